@@ -0,0 +1,140 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::menu::{GameMode, GameSpeed};
+use crate::stats::RunStats;
+
+pub const ACHIEVEMENTS_FILE: &str = "achievements.cfg";
+
+// 成就定义。progress 根据当前这一局的统计算出进度，达到 goal 即解锁
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: u32,
+    progress: fn(&RunStats) -> u32,
+}
+
+pub static ACHIEVEMENTS: [Achievement; 5] = [
+    Achievement {
+        id: "length_50",
+        name: "LONG SNAKE",
+        description: "Reach length 50",
+        goal: 50,
+        progress: |run| run.max_length as u32,
+    },
+    Achievement {
+        id: "big_food_5",
+        name: "BIG EATER",
+        description: "Eat 5 big foods in one game",
+        goal: 5,
+        progress: |run| run.big_foods,
+    },
+    Achievement {
+        id: "survive_3min",
+        name: "SURVIVOR",
+        description: "Survive 3 minutes in Survival",
+        goal: 180,
+        progress: |run| if run.mode == GameMode::Survival { run.duration as u32 } else { 0 },
+    },
+    Achievement {
+        id: "fill_half",
+        name: "HALF FULL",
+        description: "Fill 50% of the board",
+        goal: 50,
+        progress: |run| (run.max_length * 100 / run.board_cells.max(1)) as u32,
+    },
+    Achievement {
+        id: "win_fast",
+        name: "PERFECT",
+        description: "Win a game on Fast",
+        goal: 1,
+        progress: |run| (run.won && run.speed == GameSpeed::Fast) as u32,
+    },
+];
+
+// 各成就的最好进度与解锁状态，以 id.key=value 的纯文本形式保存
+#[derive(Debug, Clone, PartialEq)]
+pub struct Achievements {
+    progress: Vec<u32>,
+    unlocked: Vec<bool>,
+}
+
+impl Default for Achievements {
+    fn default() -> Self {
+        Achievements {
+            progress: vec![0; ACHIEVEMENTS.len()],
+            unlocked: vec![false; ACHIEVEMENTS.len()],
+        }
+    }
+}
+
+impl Achievements {
+    pub fn load<P: AsRef<Path>>(path: P) -> Achievements {
+        let mut achievements = Achievements::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return achievements,
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            let (id, field) = match key.trim().split_once('.') {
+                Some(f) => f,
+                None => continue,
+            };
+            let i = match ACHIEVEMENTS.iter().position(|a| a.id == id) {
+                Some(i) => i,
+                None => continue,
+            };
+            match field {
+                "progress" => achievements.progress[i] = value.trim().parse().unwrap_or(0),
+                "unlocked" => achievements.unlocked[i] = value.trim() == "true",
+                _ => {}
+            }
+        }
+        achievements
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            out.push_str(&format!("{}.progress={}\n", a.id, self.progress[i]));
+            out.push_str(&format!("{}.unlocked={}\n", a.id, self.unlocked[i]));
+        }
+        fs::write(path, out)
+    }
+
+    // 用当前这一局的统计更新进度，返回本次新解锁的成就
+    pub fn evaluate(&mut self, run: &RunStats) -> Vec<&'static Achievement> {
+        let mut newly = Vec::new();
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            let p = (a.progress)(run).min(a.goal);
+            self.progress[i] = self.progress[i].max(p);
+            if !self.unlocked[i] && p >= a.goal {
+                self.unlocked[i] = true;
+                newly.push(a);
+            }
+        }
+        newly
+    }
+
+    pub fn progress(&self, index: usize) -> u32 {
+        self.progress[index]
+    }
+
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        self.unlocked[index]
+    }
+
+    pub fn unlocked_count(&self) -> usize {
+        self.unlocked.iter().filter(|u| **u).count()
+    }
+}
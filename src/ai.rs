@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use crate::game::Game;
+use crate::keybindings::DIRECTIONS;
+use crate::rng::GameRng;
+use crate::snake::Direction;
+
+// 自动驾驶：根据当前局面决定是否转向。由调用方保证蛇每前进一格只询问一次
+pub trait Controller {
+    fn decide(&mut self, game: &Game) -> Option<Direction>;
+}
+
+// 借用的控制器（如整场比赛共用的机器人连接）也可以交给 Driver
+impl<C: Controller + ?Sized> Controller for &mut C {
+    fn decide(&mut self, game: &Game) -> Option<Direction> {
+        (**self).decide(game)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AiKind {
+    Random, // 随机选择安全的方向
+    Greedy, // 朝最近的食物走，只避开下一格的危险
+    Safe,   // 在贪心的基础上避免钻进放不下自己的区域
+}
+
+impl AiKind {
+    pub const ALL: [AiKind; 3] = [AiKind::Random, AiKind::Greedy, AiKind::Safe];
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            AiKind::Random => "random",
+            AiKind::Greedy => "greedy",
+            AiKind::Safe => "safe",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<AiKind> {
+        AiKind::ALL.iter().copied().find(|k| k.key() == key)
+    }
+
+    pub fn create(&self, seed: u64) -> Box<dyn Controller> {
+        match *self {
+            AiKind::Random => Box::new(RandomAi { rng: GameRng::new(seed) }),
+            AiKind::Greedy => Box::new(GreedyAi),
+            AiKind::Safe => Box::new(SafeAi),
+        }
+    }
+}
+
+// 不掉头且下一格安全的方向
+fn safe_directions(game: &Game) -> Vec<Direction> {
+    let current = game.head_direction();
+    DIRECTIONS
+        .iter()
+        .copied()
+        .filter(|&d| d != current.opposite() && game.is_safe(d))
+        .collect()
+}
+
+fn step((x, y): (i32, i32), d: Direction) -> (i32, i32) {
+    let (dx, dy) = d.delta();
+    (x + dx, y + dy)
+}
+
+// 普通食物与大食物占据的所有格子
+pub fn food_cells(game: &Game) -> Vec<(i32, i32)> {
+    let mut cells: Vec<(i32, i32)> = game.food_position().into_iter().collect();
+    if let Some((bx, by)) = game.big_food_position() {
+        cells.extend([(bx, by), (bx + 1, by), (bx, by + 1), (bx + 1, by + 1)]);
+    }
+    cells
+}
+
+// 到最近食物（普通食物或大食物的任一格）的曼哈顿距离
+pub fn food_distance(game: &Game, (x, y): (i32, i32)) -> i32 {
+    food_cells(game).iter().map(|(fx, fy)| (fx - x).abs() + (fy - y).abs()).min().unwrap_or(0)
+}
+
+fn turn_to(game: &Game, d: Direction) -> Option<Direction> {
+    if d == game.head_direction() {
+        None
+    } else {
+        Some(d)
+    }
+}
+
+pub struct RandomAi {
+    rng: GameRng,
+}
+
+impl Controller for RandomAi {
+    fn decide(&mut self, game: &Game) -> Option<Direction> {
+        let options = safe_directions(game);
+        // 直行安全时大多数时候保持直行，免得原地打转
+        if options.contains(&game.head_direction()) && self.rng.gen_range(0, 5) > 0 {
+            return None;
+        }
+        if options.is_empty() {
+            return None;
+        }
+        let d = options[self.rng.gen_range(0, options.len() as i32) as usize];
+        turn_to(game, d)
+    }
+}
+
+pub struct GreedyAi;
+
+impl Controller for GreedyAi {
+    fn decide(&mut self, game: &Game) -> Option<Direction> {
+        let head = game.head_position();
+        let best = safe_directions(game)
+            .into_iter()
+            .min_by_key(|&d| food_distance(game, step(head, d)))?;
+        turn_to(game, best)
+    }
+}
+
+pub struct SafeAi;
+
+impl Controller for SafeAi {
+    fn decide(&mut self, game: &Game) -> Option<Direction> {
+        let head = game.head_position();
+        let length = game.snake_length();
+        // 优先选择之后仍有足够空间的方向，其次离食物近
+        let best = safe_directions(game).into_iter().max_by_key(|&d| {
+            let next = step(head, d);
+            let area = reachable_area(game, next);
+            (area >= length, -food_distance(game, next), area)
+        })?;
+        turn_to(game, best)
+    }
+}
+
+// 从 start 出发能到达的空格数（蛇尾下一步会让开，不算障碍）
+fn reachable_area(game: &Game, start: (i32, i32)) -> usize {
+    let (w, h) = game.board_size();
+    let index = |(x, y): (i32, i32)| (y * w + x) as usize;
+    let mut blocked = vec![false; (w * h) as usize];
+    let segments = game.snake_segments();
+    for &p in &segments[..segments.len().saturating_sub(1)] {
+        blocked[index(p)] = true;
+    }
+    let mut queue = VecDeque::new();
+    blocked[index(start)] = true;
+    queue.push_back(start);
+    let mut count = 0;
+    while let Some(p) = queue.pop_front() {
+        count += 1;
+        for d in DIRECTIONS.iter() {
+            let (x, y) = step(p, *d);
+            if x > 0 && y > 0 && x < w - 1 && y < h - 1 && !blocked[index((x, y))] {
+                blocked[index((x, y))] = true;
+                queue.push_back((x, y));
+            }
+        }
+    }
+    count
+}
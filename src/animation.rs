@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageResult, RgbaImage};
+
+use crate::effects::Effects;
+use crate::events::{self, EventListener};
+use crate::game::Game;
+use crate::hud::Hud;
+use crate::menu::{Menu, MenuState};
+use crate::replay::{Replay, ReplayStep};
+use crate::scene::rasterize;
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+pub const HIGHLIGHT_SECONDS: f64 = 10.0; // 游戏中 F9 导出最近多少秒
+const CHECKPOINT_INTERVAL: f64 = 1.0; // 滚动缓冲每隔多久保存一次完整状态
+const ENCODER_SPEED: i32 = 10; // GIF 调色板量化的速度，1 最慢最精细，30 最快
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GifOptions {
+    pub scale: f64,
+    pub fps: u32,
+    pub last: Option<f64>, // 只导出最后若干秒
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions { scale: 0.5, fps: 15, last: None }
+    }
+}
+
+// snake gif 的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct GifExport {
+    pub replay: PathBuf,
+    pub output: PathBuf,
+    pub window_size: (u32, u32),
+    pub options: GifOptions,
+}
+
+// 某一时刻画面相关的全部状态
+#[derive(Clone)]
+struct Snapshot {
+    game: Game,
+    effects: Effects,
+    hud: Hud,
+}
+
+// 可以离屏重放的一段：起点状态加之后的操作
+pub struct Clip {
+    start: Snapshot,
+    steps: Vec<ReplayStep>,
+    settings: Settings,
+    window_size: (f64, f64),
+}
+
+impl Clip {
+    // 整局回放，使用默认设置的配色与显示
+    pub fn from_replay(replay: &Replay, window_size: (u32, u32)) -> Clip {
+        let (w, h) = (window_size.0 as f64, window_size.1 as f64);
+        let settings = Settings::default();
+        let mut game = replay.start();
+        game.update_window_size(w, h);
+        let mut effects = Effects::new();
+        effects.set_seed(replay.seed);
+        effects.set_theme(Theme::from_palette(settings.palette));
+        let mut hud = Hud::new(w, h);
+        hud.apply_settings(&settings);
+        Clip {
+            start: Snapshot { game, effects, hud },
+            steps: replay.steps.clone(),
+            settings,
+            window_size: (w, h),
+        }
+    }
+
+    fn duration(&self) -> f64 {
+        self.steps.iter().map(|s| if let ReplayStep::Update(dt) = s { *dt } else { 0.0 }).sum()
+    }
+
+    // 按操作重放，每隔 1/fps 秒取一帧画面写入 GIF，死亡动画放完为止；返回帧数
+    pub fn export_gif(&self, path: &Path, options: &GifOptions) -> ImageResult<usize> {
+        let mut menu = Menu::new(self.window_size.0, self.window_size.1);
+        menu.settings = self.settings.clone();
+        menu.state = MenuState::Playing;
+        let Snapshot { mut game, mut effects, mut hud } = self.start.clone();
+
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), ENCODER_SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        let delay = Delay::from_numer_denom_ms(1000, options.fps);
+        let mut encode = |game: &Game, effects: &Effects, hud: &Hud| -> ImageResult<()> {
+            let r = rasterize(&menu, Some(game), effects, hud, options.scale);
+            let buffer = RgbaImage::from_raw(r.width(), r.height(), r.pixels().to_vec()).expect("缓冲区大小与画面一致");
+            encoder.encode_frame(Frame::from_parts(buffer, 0, 0, delay))
+        };
+
+        let frame_time = 1.0 / options.fps as f64;
+        let mut next_frame = options.last.map_or(0.0, |s| (self.duration() - s).max(0.0));
+        let mut time = 0.0;
+        let mut frames = 0;
+        // 与窗口中每帧的顺序一致：特效先推进，再执行这一帧的操作并分发事件
+        for frame in self.steps.split_inclusive(|s| matches!(s, ReplayStep::Update(_))) {
+            if let Some(&ReplayStep::Update(dt)) = frame.last() {
+                effects.update(dt);
+                hud.update(dt);
+                time += dt;
+            }
+            for &step in frame {
+                step.apply(&mut game);
+            }
+            let listeners: &mut [&mut dyn EventListener] = &mut [&mut effects, &mut hud];
+            events::dispatch(&game.drain_events(), listeners);
+            if time >= next_frame {
+                encode(&game, &effects, &hud)?;
+                frames += 1;
+                while next_frame <= time {
+                    next_frame += frame_time;
+                }
+            }
+        }
+        while game.is_game_over() && effects.is_dying() {
+            effects.update(frame_time);
+            hud.update(frame_time);
+            encode(&game, &effects, &hud)?;
+            frames += 1;
+        }
+        Ok(frames)
+    }
+}
+
+// 一个检查点及其后的操作
+struct Segment {
+    start: Snapshot,
+    steps: Vec<ReplayStep>,
+    duration: f64,
+}
+
+// 当前这一局最近若干秒的滚动缓冲；只保存状态和操作，导出时才离屏绘制
+pub struct Highlights {
+    seconds: f64,
+    segments: VecDeque<Segment>,
+}
+
+impl Highlights {
+    pub fn new(seconds: f64) -> Highlights {
+        Highlights { seconds, segments: VecDeque::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+
+    // 每帧推进之前调用：距上一个检查点够久时保存一次完整状态，并丢弃用不到的旧片段
+    pub fn checkpoint(&mut self, game: &Game, effects: &Effects, hud: &Hud) {
+        if self.segments.back().is_some_and(|s| s.duration < CHECKPOINT_INTERVAL) {
+            return;
+        }
+        let start = Snapshot { game: game.clone(), effects: effects.clone(), hud: hud.clone() };
+        self.segments.push_back(Segment { start, steps: Vec::new(), duration: 0.0 });
+        while self.segments.len() > 1 && self.duration() - self.segments[0].duration >= self.seconds {
+            self.segments.pop_front();
+        }
+    }
+
+    // 记下已执行的操作；还没有检查点时忽略（检查点的状态已包含它）
+    pub fn push(&mut self, step: ReplayStep) {
+        if let Some(segment) = self.segments.back_mut() {
+            if let ReplayStep::Update(dt) = step {
+                segment.duration += dt;
+            }
+            segment.steps.push(step);
+        }
+    }
+
+    fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+
+    // 从最早的检查点开始的片段，导出时配合 GifOptions::last 截取最后几秒
+    pub fn clip(&self, settings: &Settings, window_size: (f64, f64)) -> Option<Clip> {
+        let first = self.segments.front()?;
+        Some(Clip {
+            start: first.start.clone(),
+            steps: self.segments.iter().flat_map(|s| s.steps.iter().copied()).collect(),
+            settings: settings.clone(),
+            window_size,
+        })
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.seconds
+    }
+}
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(target_os = "linux")]
+use std::thread;
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+
+use crate::events::{EventListener, FoodKind, GameEvent};
+use crate::settings::Settings;
+use crate::synth;
+
+pub const SAMPLE_RATE: u32 = 22050;
+const MIX_CHUNK: usize = 256; // 每次混音的采样数（约 12 毫秒）
+#[cfg(target_os = "linux")]
+const MAX_LEAD: Duration = Duration::from_millis(40); // 混音最多超前播放进度多久，即音效的延迟
+const MAX_PITCH_STEP: u32 = 12; // 连击升调最多升高一个八度
+
+// 游戏中会播放的音效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Eat,
+    BigEat,
+    Turn,
+    PowerUp, // 大食物出现
+    Death,
+    MenuClick,
+    TimeWarning, // 限时模式最后几秒的提示音
+}
+
+impl Sound {
+    pub const ALL: [Sound; 7] = [
+        Sound::Eat,
+        Sound::BigEat,
+        Sound::Turn,
+        Sound::PowerUp,
+        Sound::Death,
+        Sound::MenuClick,
+        Sound::TimeWarning,
+    ];
+
+    // 导出 WAV 等场合使用的名称
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Sound::Eat => "eat",
+            Sound::BigEat => "big_eat",
+            Sound::Turn => "turn",
+            Sound::PowerUp => "power_up",
+            Sound::Death => "death",
+            Sound::MenuClick => "menu_click",
+            Sound::TimeWarning => "time_warning",
+        }
+    }
+}
+
+// 每个界面循环播放的背景音乐
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Menu,
+    Game,
+    GameOver,
+}
+
+impl MusicTrack {
+    pub const ALL: [MusicTrack; 3] = [MusicTrack::Menu, MusicTrack::Game, MusicTrack::GameOver];
+
+    // assets/music/ 下对应的文件名
+    pub fn file_name(&self) -> &'static str {
+        match *self {
+            MusicTrack::Menu => "menu.wav",
+            MusicTrack::Game => "game.wav",
+            MusicTrack::GameOver => "game_over.wav",
+        }
+    }
+}
+
+struct Voice {
+    samples: Arc<Vec<f32>>,
+    pos: usize,
+}
+
+// 软件混音器：把正在播放的音效与背景音乐混成单声道 PCM
+pub struct Mixer {
+    sounds: HashMap<Sound, Arc<Vec<f32>>>,
+    music: HashMap<MusicTrack, Arc<Vec<f32>>>,
+    voices: Vec<Voice>,
+    current_music: Option<MusicTrack>,
+    music_pos: usize,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+}
+
+impl Mixer {
+    pub fn new() -> Mixer {
+        Mixer {
+            sounds: HashMap::new(),
+            music: HashMap::new(),
+            voices: Vec::new(),
+            current_music: None,
+            music_pos: 0,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+        }
+    }
+
+    pub fn set_sound(&mut self, sound: Sound, samples: Vec<f32>) {
+        self.sounds.insert(sound, Arc::new(samples));
+    }
+
+    pub fn set_music_data(&mut self, track: MusicTrack, samples: Vec<f32>) {
+        self.music.insert(track, Arc::new(samples));
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        if let Some(samples) = self.sounds.get(&sound) {
+            self.voices.push(Voice { samples: samples.clone(), pos: 0 });
+        }
+    }
+
+    // 播放一段临时生成的声音（例如随连击数升调的音效）
+    pub fn play_samples(&mut self, samples: Vec<f32>) {
+        self.voices.push(Voice { samples: Arc::new(samples), pos: 0 });
+    }
+
+    // 切换背景音乐；与当前曲目相同时不会从头播放
+    pub fn set_music(&mut self, track: Option<MusicTrack>) {
+        if self.current_music != track {
+            self.current_music = track;
+            self.music_pos = 0;
+        }
+    }
+
+    pub fn mix(&mut self, out: &mut [f32]) {
+        for s in out.iter_mut() {
+            *s = 0.0;
+        }
+        let sfx_gain = self.master_volume * self.sfx_volume;
+        for voice in &mut self.voices {
+            for s in out.iter_mut() {
+                if voice.pos >= voice.samples.len() {
+                    break;
+                }
+                *s += voice.samples[voice.pos] * sfx_gain;
+                voice.pos += 1;
+            }
+        }
+        self.voices.retain(|v| v.pos < v.samples.len());
+
+        if let Some(track) = self.current_music {
+            if let Some(samples) = self.music.get(&track) {
+                if !samples.is_empty() {
+                    let music_gain = self.master_volume * self.music_volume;
+                    for s in out.iter_mut() {
+                        *s += samples[self.music_pos] * music_gain;
+                        self.music_pos = (self.music_pos + 1) % samples.len();
+                    }
+                }
+            }
+        }
+
+        for s in out.iter_mut() {
+            *s = s.clamp(-1.0, 1.0);
+        }
+    }
+}
+
+// 音频输出后端：负责把混音器的输出送到声卡
+pub trait AudioBackend {
+    fn name(&self) -> &'static str;
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> io::Result<()>;
+}
+
+// 不输出任何声音；没有声卡的机器与测试中使用，混音器仍可手动驱动
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+
+    fn start(&mut self, _mixer: Arc<Mutex<Mixer>>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Linux 上通过 aplay 播放：后台线程持续混音，并以 16 位 PCM 写入 aplay 的标准输入。
+// 管道能积压一秒多的音频，所以按实际经过的时间混音，只比播放进度超前 MAX_LEAD
+#[cfg(target_os = "linux")]
+pub struct AplayBackend {
+    child: Option<std::process::Child>,
+    stop: Arc<AtomicBool>,
+}
+
+#[cfg(target_os = "linux")]
+impl AplayBackend {
+    pub fn new() -> AplayBackend {
+        AplayBackend {
+            child: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AudioBackend for AplayBackend {
+    fn name(&self) -> &'static str {
+        "aplay"
+    }
+
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> io::Result<()> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+        use std::time::Instant;
+
+        let rate = SAMPLE_RATE.to_string();
+        let buffer_time = MAX_LEAD.as_micros().to_string();
+        let mut child = Command::new("aplay")
+            .args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &rate, "-B", &buffer_time, "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut stdin = child.stdin.take().ok_or_else(|| io::Error::other("aplay stdin unavailable"))?;
+        let stop = self.stop.clone();
+        thread::spawn(move || {
+            let lead = (MAX_LEAD.as_secs_f64() * SAMPLE_RATE as f64) as u64;
+            let started = Instant::now();
+            let mut written = 0u64;
+            let mut buf = vec![0.0f32; MIX_CHUNK];
+            let mut bytes = Vec::with_capacity(MIX_CHUNK * 2);
+            while !stop.load(Ordering::Relaxed) {
+                let played = (started.elapsed().as_secs_f64() * SAMPLE_RATE as f64) as u64;
+                if written > played + lead {
+                    thread::sleep(Duration::from_millis(2));
+                    continue;
+                }
+                if let Ok(mut m) = mixer.lock() {
+                    m.mix(&mut buf);
+                }
+                bytes.clear();
+                for &s in &buf {
+                    bytes.extend_from_slice(&((s * i16::MAX as f32) as i16).to_le_bytes());
+                }
+                if stdin.write_all(&bytes).is_err() {
+                    break;
+                }
+                written += MIX_CHUNK as u64;
+            }
+        });
+        self.child = Some(child);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for AplayBackend {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(ref mut child) = self.child {
+            let _ = child.kill();
+        }
+    }
+}
+
+// Windows 与 macOS 上通过 cpal 播放：在声卡回调中按需混音，并重采样到设备的采样率
+#[cfg(not(target_os = "linux"))]
+pub struct CpalBackend {
+    stream: Option<cpal::Stream>,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl CpalBackend {
+    pub fn new() -> CpalBackend {
+        CpalBackend { stream: None }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl AudioBackend for CpalBackend {
+    fn name(&self) -> &'static str {
+        "cpal"
+    }
+
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> io::Result<()> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        use cpal::SampleFormat;
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| io::Error::other("no output device"))?;
+        let supported = device.default_output_config().map_err(io::Error::other)?;
+        let format = supported.sample_format();
+        let config: cpal::StreamConfig = supported.into();
+        let stream = match format {
+            SampleFormat::F32 => cpal_stream::<f32>(&device, &config, mixer),
+            SampleFormat::I16 => cpal_stream::<i16>(&device, &config, mixer),
+            SampleFormat::U16 => cpal_stream::<u16>(&device, &config, mixer),
+            other => return Err(io::Error::other(format!("unsupported sample format {}", other))),
+        }
+        .map_err(io::Error::other)?;
+        stream.play().map_err(io::Error::other)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpal_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mixer: Arc<Mutex<Mixer>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels as usize;
+    let step = SAMPLE_RATE as f64 / config.sample_rate.0 as f64;
+    let mut buf = vec![0.0f32; MIX_CHUNK];
+    let mut pos = buf.len() as f64; // 第一次回调时先混音
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            for frame in data.chunks_mut(channels) {
+                if pos >= buf.len() as f64 {
+                    pos -= buf.len() as f64;
+                    if let Ok(mut m) = mixer.lock() {
+                        m.mix(&mut buf);
+                    }
+                }
+                let sample = T::from_sample(buf[pos as usize]);
+                for out in frame.iter_mut() {
+                    *out = sample;
+                }
+                pos += step;
+            }
+        },
+        |e| eprintln!("音频输出出错: {}", e),
+        None,
+    )
+}
+
+// 音频子系统：持有混音器与输出后端，并把游戏事件映射为音效
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    backend: Box<dyn AudioBackend>,
+    eaten: Option<FoodKind>, // 刚吃到的食物；紧随其后的 ComboChanged 给出连击数，再按连击升调播放
+}
+
+impl Audio {
+    // 使用系统的音频输出（Linux 上为 aplay，其他平台为 cpal），不可用时退回静音后端
+    pub fn new() -> Audio {
+        let mixer = Arc::new(Mutex::new(Mixer::new()));
+        #[cfg(target_os = "linux")]
+        let mut backend: Box<dyn AudioBackend> = Box::new(AplayBackend::new());
+        #[cfg(not(target_os = "linux"))]
+        let mut backend: Box<dyn AudioBackend> = Box::new(CpalBackend::new());
+        if let Err(e) = backend.start(mixer.clone()) {
+            eprintln!("无法打开音频设备，使用静音模式: {}", e);
+            backend = Box::new(NullBackend);
+        }
+        Self::with_mixer(mixer, backend)
+    }
+
+    fn with_mixer(mixer: Arc<Mutex<Mixer>>, backend: Box<dyn AudioBackend>) -> Audio {
+        {
+            let mut m = mixer.lock().unwrap();
+            // 音效全部由合成器生成，不依赖音频文件
+            for sound in Sound::ALL.iter() {
+                m.set_sound(*sound, synth::sound_effect(*sound, 0));
+            }
+        }
+        let audio = Audio { mixer, backend, eaten: None };
+        audio.load_music("assets");
+        audio
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    // 从 assets/music 读取背景音乐 WAV 文件；缺少的曲目保持静音
+    pub fn load_music<P: AsRef<Path>>(&self, dir: P) {
+        let dir = dir.as_ref();
+        let mut mixer = self.mixer.lock().unwrap();
+        for track in MusicTrack::ALL.iter() {
+            if let Ok(samples) = load_wav(dir.join("music").join(track.file_name())) {
+                mixer.set_music_data(*track, samples);
+            }
+        }
+    }
+
+    pub fn play(&self, sound: Sound) {
+        self.mixer.lock().unwrap().play(sound);
+    }
+
+    pub fn set_music(&self, track: Option<MusicTrack>) {
+        self.mixer.lock().unwrap().set_music(track);
+    }
+
+    pub fn apply_settings(&self, settings: &Settings) {
+        let mut mixer = self.mixer.lock().unwrap();
+        mixer.master_volume = settings.master_volume as f32 / 100.0;
+        mixer.music_volume = settings.music_volume as f32 / 100.0;
+        mixer.sfx_volume = settings.sfx_volume as f32 / 100.0;
+    }
+}
+
+impl EventListener for Audio {
+    fn on_event(&mut self, event: &GameEvent) {
+        let sound = match event {
+            GameEvent::FoodEaten { kind, .. } => {
+                self.eaten = Some(*kind);
+                return;
+            }
+            GameEvent::ComboChanged { combo, .. } => {
+                if let Some(kind) = self.eaten.take() {
+                    let sound = if kind == FoodKind::Big { Sound::BigEat } else { Sound::Eat };
+                    let step = combo.saturating_sub(1).min(MAX_PITCH_STEP);
+                    self.mixer.lock().unwrap().play_samples(synth::sound_effect(sound, step));
+                }
+                return;
+            }
+            GameEvent::BigFoodSpawned { .. } => Sound::PowerUp,
+            GameEvent::Died { .. } => Sound::Death,
+            GameEvent::Turned { .. } => Sound::Turn,
+            GameEvent::TimeWarning { .. } => Sound::TimeWarning,
+            GameEvent::Countdown { seconds_left: 0 } => Sound::PowerUp,
+            GameEvent::Countdown { .. } => Sound::TimeWarning,
+            _ => return,
+        };
+        self.play(sound);
+    }
+}
+
+// 读取 16 位 PCM 的 WAV 文件，转为单声道并重采样到 SAMPLE_RATE
+pub fn load_wav<P: AsRef<Path>>(path: P) -> io::Result<Vec<f32>> {
+    let data = fs::read(path)?;
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+    let mut channels = 0u16;
+    let mut rate = 0u32;
+    let mut bits = 0u16;
+    let mut pcm: Option<&[u8]> = None;
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let id = &data[pos..pos + 4];
+        let size = u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]]) as usize;
+        let body_end = (pos + 8 + size).min(data.len());
+        let body = &data[pos + 8..body_end];
+        if id == b"fmt " && body.len() >= 16 {
+            channels = u16::from_le_bytes([body[2], body[3]]);
+            rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+            bits = u16::from_le_bytes([body[14], body[15]]);
+        } else if id == b"data" {
+            pcm = Some(body);
+        }
+        // 块大小为奇数时有一个填充字节
+        pos += 8 + size + (size & 1);
+    }
+    let pcm = pcm.ok_or_else(|| invalid("missing data chunk"))?;
+    if bits != 16 || channels == 0 || rate == 0 {
+        return Err(invalid("only 16-bit PCM is supported"));
+    }
+    let frame_bytes = channels as usize * 2;
+    let mono: Vec<f32> = pcm
+        .chunks_exact(frame_bytes)
+        .map(|frame| {
+            let sum: f32 = frame
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .sum();
+            sum / channels as f32
+        })
+        .collect();
+    Ok(resample(&mono, rate, SAMPLE_RATE))
+}
+
+// 线性插值重采样
+fn resample(input: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || input.is_empty() {
+        return input.to_vec();
+    }
+    let out_len = (input.len() as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..out_len)
+        .map(|i| {
+            let p = i as f64 * step;
+            let idx = p as usize;
+            let frac = (p - idx as f64) as f32;
+            let a = input[idx.min(input.len() - 1)];
+            let b = input[(idx + 1).min(input.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eat_pitch_follows_combo() {
+        let mixer = Arc::new(Mutex::new(Mixer::new()));
+        let mut audio = Audio::with_mixer(mixer.clone(), Box::new(NullBackend));
+        for combo in [5, 1] {
+            audio.on_event(&GameEvent::FoodEaten { kind: FoodKind::Normal, points: 1, pos: (3, 3) });
+            audio.on_event(&GameEvent::ComboChanged { combo, multiplier: 1, window: 2.0 });
+        }
+        let m = mixer.lock().unwrap();
+        // 连击 5 时升高 4 个半音，连击中断后的第一个食物回到原调
+        assert_eq!(*m.voices[0].samples, synth::sound_effect(Sound::Eat, 4));
+        assert_eq!(*m.voices[1].samples, synth::sound_effect(Sound::Eat, 0));
+    }
+}
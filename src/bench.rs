@@ -0,0 +1,74 @@
+use std::time::Instant;
+
+use crate::ai::AiKind;
+use crate::cli::PlayOptions;
+use crate::events::GameEvent;
+use crate::game::Level;
+use crate::menu::{GameMode, GameSpeed};
+use crate::rng::GameRng;
+use crate::sim::{self, Driver, SimOptions, SIM_DT};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    pub frames: u64,
+    pub ticks: u64, // 蛇移动的总格数
+    pub games: u32,
+    pub total_score: i64,
+    pub best_score: i32,
+    pub elapsed: f64, // 实际耗时（秒）
+}
+
+impl BenchReport {
+    pub fn print(&self) {
+        let per_sec = |n: u64| n as f64 / self.elapsed.max(1e-9);
+        println!("帧数:       {}", self.frames);
+        println!("模拟时长:   {:.1} 秒", self.frames as f64 * SIM_DT);
+        println!("实际耗时:   {:.3} 秒", self.elapsed);
+        println!("帧/秒:      {:.0}", per_sec(self.frames));
+        println!("移动格/秒:  {:.0}", per_sec(self.ticks));
+        println!("局数:       {}", self.games);
+        println!("平均得分:   {:.1}", self.total_score as f64 / self.games.max(1) as f64);
+        println!("最高得分:   {}", self.best_score);
+    }
+}
+
+// 不打开窗口，用贪心自动驾驶连续玩 frames 帧，一局结束立即开始下一局
+pub fn run(frames: u64, options: &PlayOptions) -> BenchReport {
+    let sim_options = SimOptions {
+        mode: options.mode.unwrap_or(GameMode::Classic),
+        speed: options.speed.unwrap_or(GameSpeed::Medium),
+        level: options.level.unwrap_or(Level::Normal),
+        ..SimOptions::default()
+    };
+    let mut seeds = GameRng::new(options.seed.unwrap_or(0));
+    let new_driver = |seeds: &mut GameRng| {
+        let seed = seeds.next_u64();
+        Driver::new(sim::new_game(&sim_options, seed), AiKind::Greedy.create(seed))
+    };
+
+    let mut report = BenchReport {
+        frames,
+        ticks: 0,
+        games: 1,
+        total_score: 0,
+        best_score: 0,
+        elapsed: 0.0,
+    };
+    let mut driver = new_driver(&mut seeds);
+    let start = Instant::now();
+    for _ in 0..frames {
+        driver.frame(SIM_DT);
+        report.ticks += driver.game.drain_events().iter().filter(|e| matches!(e, GameEvent::TickAdvanced { .. })).count() as u64;
+        if driver.game.is_game_over() {
+            report.total_score += driver.game.get_score() as i64;
+            report.best_score = report.best_score.max(driver.game.get_score());
+            report.games += 1;
+            driver = new_driver(&mut seeds);
+        }
+    }
+    // 最后一局未结束也计入
+    report.total_score += driver.game.get_score() as i64;
+    report.best_score = report.best_score.max(driver.game.get_score());
+    report.elapsed = start.elapsed().as_secs_f64();
+    report
+}
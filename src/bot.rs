@@ -0,0 +1,449 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ai::Controller;
+use crate::env::{Observation, ObservationKind};
+use crate::game::Game;
+use crate::keybindings::parse_direction;
+use crate::rng::GameRng;
+use crate::sim::{self, SimOptions, Summary};
+use crate::snake::Direction;
+use crate::stats::RunStats;
+
+pub const DEFAULT_DEADLINE_MS: u64 = 50;
+pub const ADDR_ENV: &str = "SNAKE_BOT_ADDR"; // 比赛时告诉机器人进程连接哪个地址
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 监听地址："unix:<路径>" 为 Unix 套接字，其余按 TCP 的 主机:端口 解析
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, std::path::PathBuf),
+}
+
+impl Listener {
+    pub fn bind(address: &str) -> io::Result<Listener> {
+        match address.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some(path) => {
+                // 上次异常退出留下的套接字文件会导致绑定失败
+                let _ = std::fs::remove_file(path);
+                Ok(Listener::Unix(UnixListener::bind(path)?, path.into()))
+            }
+            #[cfg(not(unix))]
+            Some(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "当前系统不支持 Unix 套接字")),
+            None => Ok(Listener::Tcp(TcpListener::bind(address)?)),
+        }
+    }
+
+    pub fn address(&self) -> String {
+        match self {
+            Listener::Tcp(l) => l.local_addr().map_or_else(|_| "?".to_string(), |a| a.to_string()),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => format!("unix:{}", path.display()),
+        }
+    }
+
+    // 等待一个机器人连接；timeout 为空时一直等待
+    pub fn accept(&self, deadline: Duration, timeout: Option<Duration>) -> io::Result<BotConnection> {
+        let give_up = timeout.map(|t| Instant::now() + t);
+        self.set_nonblocking(give_up.is_some())?;
+        loop {
+            let accepted = match self {
+                Listener::Tcp(l) => l.accept().and_then(|(stream, _)| {
+                    stream.set_nonblocking(false)?;
+                    stream.set_nodelay(true)?;
+                    let reader = stream.try_clone()?;
+                    Ok(BotConnection::new(reader, Box::new(stream), deadline))
+                }),
+                #[cfg(unix)]
+                Listener::Unix(l, _) => l.accept().and_then(|(stream, _)| {
+                    stream.set_nonblocking(false)?;
+                    let reader = stream.try_clone()?;
+                    Ok(BotConnection::new(reader, Box::new(stream), deadline))
+                }),
+            };
+            match accepted {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if give_up.is_some_and(|t| Instant::now() >= t) {
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, "等待机器人连接超时"));
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                }
+                other => return other,
+            }
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Listener::Tcp(l) => l.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Listener::Unix(l, _) => l.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// 与一个机器人的连接。每行一个 JSON 对象：
+//   发给机器人  {"type":"start",...}  {"type":"tick","tick":N,"state":{...}}  {"type":"end",...}
+//   机器人回复  {"direction":"up","tick":N}（tick 可省略）
+// 每格必须在 deadline 内回复，超时或断开时蛇保持直行
+pub struct BotConnection {
+    writer: Box<dyn Write + Send>,
+    replies: Receiver<Reply>,
+    deadline: Duration,
+    tick: u64,
+    last_head: Option<(i32, i32)>,
+    asked: Option<Instant>, // 本格询问发出的时间，收到回复或超时后清空
+    pub timeouts: u32,
+    pub invalid: u32, // 无法解析或方向名错误的回复
+    pub disconnected: bool,
+}
+
+impl BotConnection {
+    fn new<R: Read + Send + 'static>(reader: R, writer: Box<dyn Write + Send>, deadline: Duration) -> BotConnection {
+        // 单独的线程阻塞读取并解析回复，主线程只需取出已到达的结果
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if sender.send(Reply::parse(&line)).is_err() {
+                    break;
+                }
+            }
+        });
+        BotConnection {
+            writer,
+            replies,
+            deadline,
+            tick: 0,
+            last_head: None,
+            asked: None,
+            timeouts: 0,
+            invalid: 0,
+            disconnected: false,
+        }
+    }
+
+    fn send(&mut self, message: &str) {
+        if self.disconnected {
+            return;
+        }
+        let result = writeln!(self.writer, "{}", message).and_then(|_| self.writer.flush());
+        if result.is_err() {
+            self.disconnected = true;
+        }
+    }
+
+    // 新的一局开始时调用
+    pub fn start(&mut self, game: &Game) {
+        self.tick = 0;
+        self.last_head = None;
+        self.asked = None;
+        // 丢弃上一局遗留的迟到回复
+        while self.replies.try_recv().is_ok() {}
+        let (w, h) = game.board_size();
+        let message = format!(
+            "{{\"type\":\"start\",\"width\":{},\"height\":{},\"mode\":\"{}\",\"speed\":\"{}\",\"deadline_ms\":{}}}",
+            w,
+            h,
+            game.get_game_mode().key(),
+            game.speed().key(),
+            self.deadline.as_millis()
+        );
+        self.send(&message);
+    }
+
+    pub fn finish(&mut self, run: &RunStats) {
+        let message = format!(
+            "{{\"type\":\"end\",\"score\":{},\"length\":{},\"ticks\":{},\"duration\":{:.3},\"death\":{},\"won\":{}}}",
+            run.score,
+            run.length,
+            run.ticks,
+            run.duration,
+            run.death.map_or("null".to_string(), |c| format!("\"{}\"", c.label())),
+            run.won
+        );
+        self.send(&message);
+    }
+
+    // 蛇进入新的一格时发出询问；上一格的询问仍未回复则记一次超时
+    fn ask(&mut self, game: &Game) -> bool {
+        let head = game.head_position();
+        if self.disconnected || self.last_head == Some(head) {
+            return false;
+        }
+        if self.asked.is_some() {
+            self.timeouts += 1;
+        }
+        self.last_head = Some(head);
+        self.tick += 1;
+        let message = format!(
+            "{{\"type\":\"tick\",\"tick\":{},\"state\":{}}}",
+            self.tick,
+            Observation::from_game(game, ObservationKind::Raw).to_json()
+        );
+        self.send(&message);
+        self.asked = Some(Instant::now());
+        true
+    }
+
+    // 处理一条回复，是本格的有效回复时返回方向；过期的回复（tick 较小）直接丢弃
+    fn take(&mut self, reply: Reply) -> Option<Direction> {
+        match reply {
+            Reply::Turn(d, tick) if tick.unwrap_or(self.tick) == self.tick && self.asked.is_some() => {
+                self.asked = None;
+                Some(d)
+            }
+            Reply::Turn(..) => None, // 迟到的回复（可能来自上一局）
+            Reply::Invalid => {
+                self.invalid += 1;
+                None
+            }
+        }
+    }
+
+    // 等待本格的回复，最多等到 deadline（比赛中使用，不打开窗口）
+    fn wait_reply(&mut self) -> Option<Direction> {
+        while let Some(asked) = self.asked {
+            match self.replies.recv_timeout((asked + self.deadline).saturating_duration_since(Instant::now())) {
+                Ok(reply) => {
+                    if let Some(d) = self.take(reply) {
+                        return Some(d);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.asked = None;
+                    self.timeouts += 1;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.asked = None;
+                    self.disconnected = true;
+                }
+            }
+        }
+        None
+    }
+
+    // 窗口中每帧调用，只取出已到达的回复而不等待，画面不会因机器人变慢而卡顿；
+    // 回复与当前方向相同即为直行，不能交给 turn（那会立即多走一格）
+    pub fn poll(&mut self, game: &Game) -> Option<Direction> {
+        self.ask(game);
+        let mut direction = None;
+        while self.asked.is_some() && direction.is_none() {
+            match self.replies.try_recv() {
+                Ok(reply) => direction = self.take(reply),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.asked = None;
+                    self.disconnected = true;
+                }
+            }
+        }
+        if self.asked.is_some_and(|t| t.elapsed() >= self.deadline) {
+            self.asked = None;
+            self.timeouts += 1;
+        }
+        direction.filter(|&d| d != game.head_direction())
+    }
+}
+
+// 读取线程解析好的一条回复
+enum Reply {
+    Turn(Direction, Option<u64>), // tick 省略时视为当前格
+    Invalid,                      // 无法解析或方向名错误
+}
+
+impl Reply {
+    fn parse(line: &str) -> Reply {
+        let direction = json_value(line, "direction").and_then(parse_direction);
+        let tick = json_value(line, "tick").map(|t| t.parse::<u64>());
+        match (direction, tick) {
+            (Some(d), None) => Reply::Turn(d, None),
+            (Some(d), Some(Ok(t))) => Reply::Turn(d, Some(t)),
+            _ => Reply::Invalid,
+        }
+    }
+}
+
+impl Controller for BotConnection {
+    // 蛇每前进一格询问一次并等待回复；同一格内重复调用直接返回
+    fn decide(&mut self, game: &Game) -> Option<Direction> {
+        if !self.ask(game) {
+            return None;
+        }
+        self.wait_reply().filter(|&d| d != game.head_direction())
+    }
+}
+
+// 取出一行 JSON 中某个键的值（字符串去掉引号），只支持本协议用到的平铺对象
+fn json_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let quoted = format!("\"{}\"", key);
+    let rest = line[line.find(&quoted)? + quoted.len()..].trim_start().strip_prefix(':')?.trim_start();
+    match rest.strip_prefix('"') {
+        Some(s) => s.split('"').next(),
+        None => rest.split([',', '}']).next().map(str::trim),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentOptions {
+    pub bots: Vec<String>, // 启动各个机器人的命令，按空白分隔程序与参数
+    pub deadline: Duration,
+    pub sim: SimOptions, // 局数、模式、速度、关卡、种子、最长时间与平衡参数
+}
+
+impl Default for TournamentOptions {
+    fn default() -> Self {
+        TournamentOptions {
+            bots: Vec::new(),
+            deadline: Duration::from_millis(DEFAULT_DEADLINE_MS),
+            sim: SimOptions {
+                games: 20,
+                ..SimOptions::default()
+            },
+        }
+    }
+}
+
+pub struct Standing {
+    pub command: String,
+    pub result: Result<Summary, String>,
+    pub timeouts: u32,
+    pub invalid: u32,
+}
+
+// 每个机器人在各自的线程中用同一组种子依次玩 games 局，按平均得分排名
+pub fn run_tournament(options: &TournamentOptions) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = thread::scope(|s| {
+        let handles: Vec<_> = options.bots.iter().map(|command| s.spawn(move || play_bot(command, options))).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let mean = |s: &Standing| s.result.as_ref().map_or(f64::NEG_INFINITY, |r| r.mean_score);
+    standings.sort_by(|a, b| mean(b).partial_cmp(&mean(a)).unwrap());
+    standings
+}
+
+fn play_bot(command: &str, options: &TournamentOptions) -> Standing {
+    let mut standing = Standing {
+        command: command.to_string(),
+        result: Err(String::new()),
+        timeouts: 0,
+        invalid: 0,
+    };
+    let listener = match Listener::bind("127.0.0.1:0") {
+        Ok(l) => l,
+        Err(e) => {
+            standing.result = Err(format!("无法监听: {}", e));
+            return standing;
+        }
+    };
+    let mut child = match spawn_bot(command, &listener.address()) {
+        Ok(c) => c,
+        Err(e) => {
+            standing.result = Err(format!("无法启动: {}", e));
+            return standing;
+        }
+    };
+    let mut conn = match listener.accept(options.deadline, Some(CONNECT_TIMEOUT)) {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            standing.result = Err(e.to_string());
+            return standing;
+        }
+    };
+
+    let mut seeds = GameRng::new(options.sim.seed);
+    let start = Instant::now();
+    let mut runs = Vec::new();
+    for _ in 0..options.sim.games {
+        let game = sim::new_game(&options.sim, seeds.next_u64());
+        conn.start(&game);
+        let run = sim::play(&options.sim, game, Box::new(&mut conn));
+        conn.finish(&run);
+        runs.push(run);
+    }
+    standing.result = Ok(Summary::from_runs(&runs, start.elapsed().as_secs_f64()));
+    standing.timeouts = conn.timeouts;
+    standing.invalid = conn.invalid;
+    drop(conn);
+    let _ = child.kill();
+    let _ = child.wait();
+    standing
+}
+
+fn spawn_bot(command: &str, address: &str) -> io::Result<Child> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "命令为空"))?;
+    Command::new(program)
+        .args(parts)
+        .env(ADDR_ENV, address)
+        .stdin(Stdio::null())
+        .spawn()
+}
+
+pub fn print_standings(standings: &[Standing]) {
+    for (i, s) in standings.iter().enumerate() {
+        match &s.result {
+            Ok(r) => {
+                println!("第 {} 名  {}", i + 1, s.command);
+                println!("  平均得分 {:.2}  最高 {}  平均存活 {:.1} 秒", r.mean_score, r.max_score, r.mean_duration);
+                println!(
+                    "  撞墙 {}  咬到自己 {}  时间耗尽 {}  回复超时 {} 次  无效回复 {} 条",
+                    r.wall, r.self_collision, r.timeout, s.timeouts, s.invalid
+                );
+            }
+            Err(e) => println!("出错    {}（{}）", s.command, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keybindings::direction_key;
+    use crate::menu::{GameMode, GameSpeed};
+    use std::net::TcpStream;
+
+    #[test]
+    fn poll_does_not_wait_for_slow_bot() {
+        let listener = Listener::bind("127.0.0.1:0").unwrap();
+        let mut bot = TcpStream::connect(listener.address()).unwrap();
+        let mut conn = listener.accept(Duration::from_millis(200), Some(CONNECT_TIMEOUT)).unwrap();
+        let game = Game::new_seeded(20, 20, GameMode::Classic, GameSpeed::Medium, 1);
+        conn.start(&game);
+
+        // 机器人还没回复时立即返回，且未到时限不算超时
+        let started = Instant::now();
+        assert_eq!(conn.poll(&game), None);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(conn.timeouts, 0);
+
+        let turn = game.head_direction().left();
+        writeln!(bot, "{{\"direction\":\"{}\",\"tick\":0}}", direction_key(turn)).unwrap(); // 过期
+        writeln!(bot, "not json").unwrap();
+        writeln!(bot, "{{\"direction\":\"{}\",\"tick\":1}}", direction_key(turn)).unwrap();
+        let mut reply = None;
+        while reply.is_none() && started.elapsed() < Duration::from_millis(150) {
+            reply = conn.poll(&game);
+        }
+        assert_eq!(reply, Some(turn));
+        assert_eq!((conn.timeouts, conn.invalid), (0, 1));
+    }
+}
@@ -0,0 +1,628 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::ai::AiKind;
+use crate::bot::{TournamentOptions, DEFAULT_DEADLINE_MS};
+use crate::env::{EnvOptions, ObservationKind};
+use crate::game::{Level, Tuning};
+use crate::menu::{GameMode, GameSpeed};
+use crate::animation::{GifExport, GifOptions};
+use crate::render::MAX_OUTPUT_SIDE;
+use crate::scene::{screen_from_key, RenderOptions};
+use crate::sim::{OutputFormat, SimOptions};
+
+pub const HELP: &str = "\
+贪吃蛇游戏
+
+用法: snake [选项]
+
+开始游戏（给出其中任意一项即跳过主菜单直接开始）:
+  --mode <classic|speed|survival>   游戏模式，默认 classic
+  --speed <slow|medium|fast>        速度，默认 medium
+  --level <small|normal|large>      关卡（游戏区域 20x20 / 30x30 / 40x40），默认 normal
+  --seed <数字>                     随机数种子，相同种子与操作得到相同的一局
+
+窗口与玩家:
+  --size <宽x高>                    窗口大小，默认 800x600
+  --fullscreen                      全屏
+  --profile <玩家名>                以指定的本地玩家启动
+  --tui                             在终端中游戏（ANSI 彩色字符界面，方向键或绑定的按键操作；依赖 stty，仅支持类 Unix 系统）
+
+回放:
+  --record <文件>                   把每一局结束时的回放写入文件
+  --replay <文件>                   播放回放文件
+
+外部机器人:
+  --bot <地址>                      等待机器人连接后由它操控，例如 127.0.0.1:7878 或 unix:/tmp/snake.sock
+  --bot-deadline <毫秒>             机器人每格的回复时限，超时则保持直行，默认 50
+
+批量模拟（不打开窗口）: snake simulate [选项]
+  --games <数量>                    模拟的局数，默认 100
+  --mode / --speed / --level        同上
+  --seed <数字>                     起始种子，默认 0；相同参数的两次模拟结果相同
+  --ai <random|greedy|safe>         自动驾驶，默认 safe
+  --max-time <秒>                   每局最长模拟时间，默认 600
+  --format <table|csv|json>         输出格式，默认 table
+  --set <参数>=<值>                 覆盖平衡参数，可重复；参数为 time_limit、time_add_per_food、
+                                    big_food_spawn_interval、big_food_lifetime、speed_ramp
+
+强化学习环境（不打开窗口）: snake env [选项]
+  --envs <数量>                     同时运行的环境数，默认 8
+  --mode / --speed / --level / --set  同上
+  --seed <数字>                     起始种子，默认 0
+  --obs <grid|features|raw>         观察：网格张量、特征向量或原始状态，默认 features
+  --reward <项>=<值>                奖励塑形，可重复；项为 food、death、step、win、approach
+  --max-steps <数量>                一局最多走的格数，默认 10000
+  --steps <数量>                    用随机动作测试时合计的步数，默认 100000
+  --serve                           通过标准输入输出与训练程序交互（每行一条命令 / 一个 JSON）
+
+机器人比赛（不打开窗口，各机器人分别单独游戏，不在同一棋盘上对战）: snake tournament --bot <命令> --bot <命令> ... [选项]
+  --bot <命令>                      启动机器人的命令，可重复；机器人从环境变量 SNAKE_BOT_ADDR 得到连接地址
+  --games <数量>                    每个机器人玩的局数（各机器人使用同一组种子，按平均得分排名），默认 20
+  --mode / --speed / --level / --seed / --max-time / --set  同 simulate
+  --deadline <毫秒>                 每格的回复时限，默认 50
+
+离屏渲染（不打开窗口）: snake render [选项] [输出.png]
+  --screen <界面>                   main、modes、speeds、confirm、settings、profile、achievements、
+                                    players、controls、playing、game-menu、game-over，默认 playing
+  --mode / --speed / --level        同上
+  --seed <数字>                     游戏画面的种子，默认 0；由自动驾驶玩到 --tick 指定的格数
+  --tick <数量>                     默认 0，即刚开局
+  --size <宽x高>                    窗口大小，默认 800x600
+  --scale <倍数>                    整体缩放，默认 1
+  --calls                           输出每条绘制调用（每行一条），默认输出画面尺寸与像素摘要
+  <输出.png>                        同时把画面写入 PNG 文件（不能与 --calls 同时使用）
+
+回放导出 GIF（不打开窗口）: snake gif <回放文件> <输出.gif> [选项]
+  --size <宽x高>                    窗口大小，默认 800x600
+  --scale <倍数>                    整体缩放，默认 0.5
+  --fps <帧率>                      每秒帧数，1 到 50，默认 15
+  --last <秒>                       只导出最后若干秒，默认整局
+
+其他:
+  --bench [帧数]                    不打开窗口，用自动驾驶跑指定帧数（默认 1000000）并输出性能
+  --export-sounds <目录>            把合成的音效导出为 WAV 后退出
+  -h, --help                        显示本帮助
+";
+
+const DEFAULT_BENCH_FRAMES: u64 = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(&'static str),
+    InvalidValue { option: &'static str, value: String, expected: &'static str },
+    Conflict(&'static str, &'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(o) => write!(f, "未知选项 {}", o),
+            CliError::MissingValue(o) => write!(f, "{} 需要一个值", o),
+            CliError::InvalidValue { option, value, expected } => {
+                write!(f, "{} 的值 \"{}\" 无效，应为 {}", option, value, expected)
+            }
+            CliError::Conflict(a, b) => write!(f, "{} 不能与 {} 同时使用", a, b),
+        }
+    }
+}
+
+// 启动时的游戏参数；mode 与 speed 为空表示使用菜单中的选择，level 为空表示 normal
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayOptions {
+    pub mode: Option<GameMode>,
+    pub speed: Option<GameSpeed>,
+    pub level: Option<Level>,
+    pub seed: Option<u64>,
+    pub start_directly: bool,
+    pub window_size: (u32, u32),
+    pub fullscreen: bool,
+    pub profile: Option<String>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub bot: Option<String>,
+    pub bot_deadline: Duration,
+    pub tui: bool,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        PlayOptions {
+            mode: None,
+            speed: None,
+            level: None,
+            seed: None,
+            start_directly: false,
+            window_size: (800, 600),
+            fullscreen: false,
+            profile: None,
+            record: None,
+            replay: None,
+            bot: None,
+            bot_deadline: Duration::from_millis(DEFAULT_DEADLINE_MS),
+            tui: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Play(PlayOptions),
+    Bench { frames: u64, options: PlayOptions },
+    Simulate(SimOptions),
+    Env(EnvOptions),
+    Tournament(TournamentOptions),
+    Render(RenderOptions),
+    Gif(GifExport),
+    ExportSounds(String),
+    Help,
+}
+
+// 解析命令行参数（不含程序名）
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    if args.first().map(String::as_str) == Some("simulate") {
+        return parse_simulate(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("env") {
+        return parse_env(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("tournament") {
+        return parse_tournament(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("render") {
+        return parse_render(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("gif") {
+        return parse_gif(&args[1..]);
+    }
+    let mut options = PlayOptions::default();
+    let mut bench: Option<u64> = None;
+    let mut export: Option<String> = None;
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        if let Some(common) = parse_common(arg, &mut value) {
+            match common? {
+                CommonOption::Mode(mode) => options.mode = Some(mode),
+                CommonOption::Speed(speed) => options.speed = Some(speed),
+                CommonOption::Level(level) => options.level = Some(level),
+                CommonOption::Seed(seed) => options.seed = Some(seed),
+                CommonOption::Set(..) => return Err(CliError::UnknownOption(arg.clone())),
+            }
+            options.start_directly = true;
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--size" => {
+                let v = value("--size")?;
+                options.window_size = parse_size(&v).ok_or_else(|| invalid("--size", v, "宽x高，例如 1024x768"))?;
+            }
+            "--fullscreen" => options.fullscreen = true,
+            "--tui" => options.tui = true,
+            "--profile" => options.profile = Some(value("--profile")?),
+            "--record" => options.record = Some(PathBuf::from(value("--record")?)),
+            "--replay" => options.replay = Some(PathBuf::from(value("--replay")?)),
+            "--export-sounds" => export = Some(value("--export-sounds")?),
+            "--bot" => {
+                options.bot = Some(value("--bot")?);
+                options.start_directly = true;
+            }
+            "--bot-deadline" => {
+                let v = value("--bot-deadline")?;
+                options.bot_deadline = parse_millis(&v).ok_or_else(|| invalid("--bot-deadline", v, "正整数（毫秒）"))?;
+            }
+            "--bench" => {
+                // 帧数可以省略
+                let frames = match iter.peek() {
+                    Some(v) if !v.starts_with('-') => {
+                        let v = iter.next().unwrap().clone();
+                        v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--bench", v, "正整数"))?
+                    }
+                    _ => DEFAULT_BENCH_FRAMES,
+                };
+                bench = Some(frames);
+            }
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+
+    if options.replay.is_some() {
+        // 回放文件中已经记录了开局参数
+        for (set, name) in [
+            (options.mode.is_some(), "--mode"),
+            (options.speed.is_some(), "--speed"),
+            (options.level.is_some(), "--level"),
+            (options.seed.is_some(), "--seed"),
+            (options.record.is_some(), "--record"),
+            (options.bot.is_some(), "--bot"),
+        ] {
+            if set {
+                return Err(CliError::Conflict("--replay", name));
+            }
+        }
+    }
+    if options.tui {
+        // 终端版没有窗口，也不录制或播放回放
+        for (set, name) in [
+            (options.fullscreen, "--fullscreen"),
+            (options.window_size != PlayOptions::default().window_size, "--size"),
+            (options.record.is_some(), "--record"),
+            (options.replay.is_some(), "--replay"),
+            (options.bot.is_some(), "--bot"),
+            (bench.is_some(), "--bench"),
+        ] {
+            if set {
+                return Err(CliError::Conflict("--tui", name));
+            }
+        }
+    }
+    if let Some(frames) = bench {
+        for (set, name) in [
+            (options.replay.is_some(), "--replay"),
+            (options.record.is_some(), "--record"),
+            (options.fullscreen, "--fullscreen"),
+            (options.profile.is_some(), "--profile"),
+            (export.is_some(), "--export-sounds"),
+            (options.bot.is_some(), "--bot"),
+        ] {
+            if set {
+                return Err(CliError::Conflict("--bench", name));
+            }
+        }
+        return Ok(Command::Bench { frames, options });
+    }
+    if let Some(dir) = export {
+        return Ok(Command::ExportSounds(dir));
+    }
+    Ok(Command::Play(options))
+}
+
+fn parse_simulate(args: &[String]) -> Result<Command, CliError> {
+    let mut options = SimOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        if let Some(common) = parse_common(arg, &mut value) {
+            match common? {
+                CommonOption::Mode(mode) => options.mode = mode,
+                CommonOption::Speed(speed) => options.speed = speed,
+                CommonOption::Level(level) => options.level = level,
+                CommonOption::Seed(seed) => options.seed = seed,
+                CommonOption::Set(key, val) => {
+                    options.tuning.set(&key, &val);
+                }
+            }
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--games" => {
+                let v = value("--games")?;
+                options.games = v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--games", v, "正整数"))?;
+            }
+            "--ai" => {
+                let v = value("--ai")?;
+                options.ai = AiKind::from_key(&v).ok_or_else(|| invalid("--ai", v, "random、greedy 或 safe"))?;
+            }
+            "--max-time" => {
+                let v = value("--max-time")?;
+                options.max_time = v.parse().ok().filter(|t: &f64| t.is_finite() && *t > 0.0).ok_or_else(|| invalid("--max-time", v, "正数"))?;
+            }
+            "--format" => {
+                let v = value("--format")?;
+                options.format = OutputFormat::from_key(&v).ok_or_else(|| invalid("--format", v, "table、csv 或 json"))?;
+            }
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+    Ok(Command::Simulate(options))
+}
+
+fn parse_env(args: &[String]) -> Result<Command, CliError> {
+    let mut options = EnvOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        if let Some(common) = parse_common(arg, &mut value) {
+            match common? {
+                CommonOption::Mode(mode) => options.config.mode = mode,
+                CommonOption::Speed(speed) => options.config.speed = speed,
+                CommonOption::Level(level) => options.config.level = level,
+                CommonOption::Seed(seed) => options.seed = seed,
+                CommonOption::Set(key, val) => {
+                    options.config.tuning.set(&key, &val);
+                }
+            }
+            continue;
+        }
+        let config = &mut options.config;
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--envs" => {
+                let v = value("--envs")?;
+                options.envs = v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--envs", v, "正整数"))?;
+            }
+            "--steps" => {
+                let v = value("--steps")?;
+                options.steps = v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--steps", v, "正整数"))?;
+            }
+            "--obs" => {
+                let v = value("--obs")?;
+                config.observation = ObservationKind::from_key(&v).ok_or_else(|| invalid("--obs", v, "grid、features 或 raw"))?;
+            }
+            "--max-steps" => {
+                let v = value("--max-steps")?;
+                config.max_steps = v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--max-steps", v, "正整数"))?;
+            }
+            "--reward" => {
+                let v = value("--reward")?;
+                let ok = match v.split_once('=') {
+                    Some((key, val)) => config.reward.set(key.trim(), val.trim()),
+                    None => false,
+                };
+                if !ok {
+                    return Err(invalid("--reward", v, "项=数值，项见 --help"));
+                }
+            }
+            "--serve" => options.serve = true,
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+    Ok(Command::Env(options))
+}
+
+fn parse_tournament(args: &[String]) -> Result<Command, CliError> {
+    let mut options = TournamentOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        if let Some(common) = parse_common(arg, &mut value) {
+            let sim = &mut options.sim;
+            match common? {
+                CommonOption::Mode(mode) => sim.mode = mode,
+                CommonOption::Speed(speed) => sim.speed = speed,
+                CommonOption::Level(level) => sim.level = level,
+                CommonOption::Seed(seed) => sim.seed = seed,
+                CommonOption::Set(key, val) => {
+                    sim.tuning.set(&key, &val);
+                }
+            }
+            continue;
+        }
+        let sim = &mut options.sim;
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--bot" => options.bots.push(value("--bot")?),
+            "--games" => {
+                let v = value("--games")?;
+                sim.games = v.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid("--games", v, "正整数"))?;
+            }
+            "--max-time" => {
+                let v = value("--max-time")?;
+                sim.max_time = v.parse().ok().filter(|t: &f64| t.is_finite() && *t > 0.0).ok_or_else(|| invalid("--max-time", v, "正数"))?;
+            }
+            "--deadline" => {
+                let v = value("--deadline")?;
+                options.deadline = parse_millis(&v).ok_or_else(|| invalid("--deadline", v, "正整数（毫秒）"))?;
+            }
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+    if options.bots.is_empty() {
+        return Err(CliError::MissingValue("--bot"));
+    }
+    Ok(Command::Tournament(options))
+}
+
+fn invalid(option: &'static str, value: String, expected: &'static str) -> CliError {
+    CliError::InvalidValue { option, value, expected }
+}
+
+// 各子命令共用的开局选项 --mode / --speed / --level / --seed / --set
+#[derive(Debug, Clone, PartialEq)]
+enum CommonOption {
+    Mode(GameMode),
+    Speed(GameSpeed),
+    Level(Level),
+    Seed(u64),
+    Set(String, String), // 已确认是 Tuning 能接受的参数与取值
+}
+
+// 解析共用选项，保证各子命令的校验与错误提示一致；arg 不是共用选项时返回 None
+fn parse_common<F>(arg: &str, value: &mut F) -> Option<Result<CommonOption, CliError>>
+where
+    F: FnMut(&'static str) -> Result<String, CliError>,
+{
+    let parsed = match arg {
+        "--mode" => value("--mode").and_then(|v| {
+            GameMode::from_key(&v).map(CommonOption::Mode).ok_or_else(|| invalid("--mode", v, "classic、speed 或 survival"))
+        }),
+        "--speed" => value("--speed").and_then(|v| {
+            GameSpeed::from_key(&v).map(CommonOption::Speed).ok_or_else(|| invalid("--speed", v, "slow、medium 或 fast"))
+        }),
+        "--level" => value("--level").and_then(|v| {
+            Level::from_key(&v).map(CommonOption::Level).ok_or_else(|| invalid("--level", v, "small、normal 或 large"))
+        }),
+        "--seed" => value("--seed").and_then(|v| v.parse().map(CommonOption::Seed).map_err(|_| invalid("--seed", v, "非负整数"))),
+        "--set" => value("--set").and_then(|v| {
+            let pair = v.split_once('=').map(|(key, val)| (key.trim().to_string(), val.trim().to_string()));
+            match pair {
+                Some((key, val)) if Tuning::default().set(&key, &val) => Ok(CommonOption::Set(key, val)),
+                _ => Err(invalid("--set", v, "参数=正数，参数见 --help")),
+            }
+        }),
+        _ => return None,
+    };
+    Some(parsed)
+}
+
+fn parse_render(args: &[String]) -> Result<Command, CliError> {
+    let mut options = RenderOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        if let Some(common) = parse_common(arg, &mut value) {
+            match common? {
+                CommonOption::Mode(mode) => options.mode = mode,
+                CommonOption::Speed(speed) => options.speed = speed,
+                CommonOption::Level(level) => options.level = level,
+                CommonOption::Seed(seed) => options.seed = seed,
+                CommonOption::Set(..) => return Err(CliError::UnknownOption(arg.clone())),
+            }
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--screen" => {
+                let v = value("--screen")?;
+                options.screen = screen_from_key(&v).ok_or_else(|| invalid("--screen", v, "界面名，见 --help"))?;
+            }
+            "--tick" => {
+                let v = value("--tick")?;
+                options.tick = v.parse().map_err(|_| invalid("--tick", v, "非负整数"))?;
+            }
+            "--size" => {
+                let v = value("--size")?;
+                options.window_size = parse_size(&v).ok_or_else(|| invalid("--size", v, "宽x高，例如 1024x768"))?;
+            }
+            "--scale" => {
+                let v = value("--scale")?;
+                options.scale = v.parse().ok().filter(|s: &f64| (0.25..=4.0).contains(s)).ok_or_else(|| invalid("--scale", v, "0.25 到 4 之间的数"))?;
+            }
+            "--calls" => options.calls = true,
+            path if !path.starts_with('-') && options.output.is_none() => options.output = Some(PathBuf::from(path)),
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+    if options.calls && options.output.is_some() {
+        return Err(CliError::Conflict("--calls", "输出文件"));
+    }
+    check_output_size(options.window_size, options.scale)?;
+    Ok(Command::Render(options))
+}
+
+fn parse_gif(args: &[String]) -> Result<Command, CliError> {
+    let mut options = GifOptions::default();
+    let mut window_size = (800, 600);
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |option: &'static str| iter.next().cloned().ok_or(CliError::MissingValue(option));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--size" => {
+                let v = value("--size")?;
+                window_size = parse_size(&v).ok_or_else(|| invalid("--size", v, "宽x高，例如 1024x768"))?;
+            }
+            "--scale" => {
+                let v = value("--scale")?;
+                options.scale = v.parse().ok().filter(|s: &f64| (0.25..=4.0).contains(s)).ok_or_else(|| invalid("--scale", v, "0.25 到 4 之间的数"))?;
+            }
+            "--fps" => {
+                let v = value("--fps")?;
+                options.fps = v.parse().ok().filter(|f| (1..=50).contains(f)).ok_or_else(|| invalid("--fps", v, "1 到 50 之间的整数"))?;
+            }
+            "--last" => {
+                let v = value("--last")?;
+                options.last = Some(v.parse().ok().filter(|s: &f64| *s > 0.0).ok_or_else(|| invalid("--last", v, "正数"))?);
+            }
+            path if !path.starts_with('-') && paths.len() < 2 => paths.push(PathBuf::from(path)),
+            other => return Err(CliError::UnknownOption(other.to_string())),
+        }
+    }
+    check_output_size(window_size, options.scale)?;
+    let mut paths = paths.into_iter();
+    match (paths.next(), paths.next()) {
+        (Some(replay), Some(output)) => Ok(Command::Gif(GifExport { replay, output, window_size, options })),
+        (Some(_), None) => Err(CliError::MissingValue("<输出.gif>")),
+        (None, _) => Err(CliError::MissingValue("<回放文件>")),
+    }
+}
+
+// 放大后的画面不能超过软件光栅化的最大边长
+fn check_output_size(window_size: (u32, u32), scale: f64) -> Result<(), CliError> {
+    if (window_size.0.max(window_size.1) as f64 * scale).round() > MAX_OUTPUT_SIDE as f64 {
+        return Err(invalid("--scale", scale.to_string(), "使放大后的宽高不超过 8192 的倍数"));
+    }
+    Ok(())
+}
+
+fn parse_millis(value: &str) -> Option<Duration> {
+    value.parse().ok().filter(|&ms| ms > 0).map(Duration::from_millis)
+}
+
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (w, h) = value.split_once('x')?;
+    let (w, h) = (w.parse::<u32>().ok()?, h.parse::<u32>().ok()?);
+    if (200..=8192).contains(&w) && (200..=8192).contains(&h) {
+        Some((w, h))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn common_options_validate_the_same_everywhere() {
+        for prefix in ["", "simulate", "env", "tournament --bot x", "render"] {
+            let err = parse(&args(&format!("{} --speed warp", prefix))).unwrap_err();
+            assert_eq!(err, invalid("--speed", "warp".to_string(), "slow、medium 或 fast"), "{}", prefix);
+            let err = parse(&args(&format!("{} --seed", prefix))).unwrap_err();
+            assert_eq!(err, CliError::MissingValue("--seed"), "{}", prefix);
+        }
+        for prefix in ["simulate", "env", "tournament --bot x"] {
+            let err = parse(&args(&format!("{} --set time_limit=-1", prefix))).unwrap_err();
+            assert_eq!(err, invalid("--set", "time_limit=-1".to_string(), "参数=正数，参数见 --help"), "{}", prefix);
+        }
+        assert_eq!(parse(&args("render --set time_limit=5")).unwrap_err(), CliError::UnknownOption("--set".to_string()));
+    }
+
+    #[test]
+    fn common_options_are_applied() {
+        let expected_tuning = Tuning { time_limit: 45.0, ..Tuning::default() };
+        match parse(&args("simulate --mode survival --level small --seed 9 --set time_limit=45")).unwrap() {
+            Command::Simulate(o) => {
+                assert_eq!((o.mode, o.level, o.seed, o.tuning), (GameMode::Survival, Level::Small, 9, expected_tuning));
+            }
+            other => panic!("{:?}", other),
+        }
+        match parse(&args("env --speed fast --seed 4 --set time_limit=45")).unwrap() {
+            Command::Env(o) => assert_eq!((o.config.speed, o.seed, o.config.tuning), (GameSpeed::Fast, 4, expected_tuning)),
+            other => panic!("{:?}", other),
+        }
+        match parse(&args("--mode speed --seed 2")).unwrap() {
+            Command::Play(o) => assert_eq!((o.mode, o.seed, o.start_directly), (Some(GameMode::Speed), Some(2), true)),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn replay_rejects_start_options() {
+        // 即使给的是默认值也算冲突
+        for (extra, name) in [
+            ("--mode classic", "--mode"),
+            ("--speed medium", "--speed"),
+            ("--level normal", "--level"),
+            ("--seed 0", "--seed"),
+            ("--record r", "--record"),
+            ("--bot 127.0.0.1:1", "--bot"),
+        ] {
+            let err = parse(&args(&format!("--replay f {}", extra))).unwrap_err();
+            assert_eq!(err, CliError::Conflict("--replay", name), "{}", extra);
+        }
+        match parse(&args("--replay f")).unwrap() {
+            Command::Play(o) => assert_eq!((o.replay, o.level), (Some(PathBuf::from("f")), None)),
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -1,7 +1,22 @@
 use piston_window::types::Color;
-use piston_window::{rectangle, Context, G2d};
+use crate::render::Renderer;
 const BLOCK_SIZE: f64 = 25.0;
 
+// 游戏区域在窗口中的布局：格子大小与居中偏移
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardLayout {
+    pub block_size: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl BoardLayout {
+    // 格子坐标（允许小数，用于插值）对应的窗口像素坐标（格子左上角）
+    pub fn to_screen(self, x: f64, y: f64) -> (f64, f64) {
+        (self.offset_x + x * self.block_size, self.offset_y + y * self.block_size)
+    }
+}
+
 pub fn to_coord(game_coord: i32) -> f64 {
     (game_coord as f64) * BLOCK_SIZE
 }
@@ -14,36 +29,30 @@ pub fn to_coord_dynamic(game_coord: i32, block_size: f64) -> f64 {
     (game_coord as f64) * block_size
 }
 
-pub fn draw_block(color: Color, x: i32, y: i32, con: &Context, g: &mut G2d) {
+pub fn draw_block(color: Color, x: i32, y: i32, r: &mut dyn Renderer) {
     let gui_x = to_coord(x);
     let gui_y = to_coord(y);
-    rectangle(
+    r.fill_rect(
         color,
         [gui_x, gui_y, BLOCK_SIZE, BLOCK_SIZE],
-        con.transform,
-        g,
     );
 }
 
-pub fn draw_block_dynamic(color: Color, x: i32, y: i32, block_size: f64, con: &Context, g: &mut G2d) {
+pub fn draw_block_dynamic(color: Color, x: i32, y: i32, block_size: f64, r: &mut dyn Renderer) {
     let gui_x = to_coord_dynamic(x, block_size);
     let gui_y = to_coord_dynamic(y, block_size);
-    rectangle(
+    r.fill_rect(
         color,
         [gui_x, gui_y, block_size, block_size],
-        con.transform,
-        g,
     );
 }
 
-pub fn draw_block_dynamic_with_offset(color: Color, x: i32, y: i32, block_size: f64, offset_x: f64, offset_y: f64, con: &Context, g: &mut G2d) {
+pub fn draw_block_dynamic_with_offset(color: Color, x: i32, y: i32, block_size: f64, offset_x: f64, offset_y: f64, r: &mut dyn Renderer) {
     let gui_x = to_coord_dynamic(x, block_size) + offset_x;
     let gui_y = to_coord_dynamic(y, block_size) + offset_y;
-    rectangle(
+    r.fill_rect(
         color,
         [gui_x, gui_y, block_size, block_size],
-        con.transform,
-        g,
     );
 }
 
@@ -53,21 +62,18 @@ pub fn draw_rectangle(
     y: i32,
     width: i32,
     height: i32,
-    con: &Context,
-    g: &mut G2d,
+    r: &mut dyn Renderer,
 ) {
     let x = to_coord(x);
     let y = to_coord(y);
-    rectangle(
+    r.fill_rect(
         color,
         [
             x,
             y,
             BLOCK_SIZE * (width as f64),
             BLOCK_SIZE * (height as f64),
-        ],
-        con.transform,
-        g,
+        ]
     );
 }
 
@@ -78,21 +84,18 @@ pub fn draw_rectangle_dynamic(
     width: i32,
     height: i32,
     block_size: f64,
-    con: &Context,
-    g: &mut G2d,
+    r: &mut dyn Renderer,
 ) {
     let x = to_coord_dynamic(x, block_size);
     let y = to_coord_dynamic(y, block_size);
-    rectangle(
+    r.fill_rect(
         color,
         [
             x,
             y,
             block_size * (width as f64),
             block_size * (height as f64),
-        ],
-        con.transform,
-        g,
+        ]
     );
 }
 
@@ -105,20 +108,17 @@ pub fn draw_rectangle_dynamic_with_offset(
     block_size: f64,
     offset_x: f64,
     offset_y: f64,
-    con: &Context,
-    g: &mut G2d,
+    r: &mut dyn Renderer,
 ) {
     let x = to_coord_dynamic(x, block_size) + offset_x;
     let y = to_coord_dynamic(y, block_size) + offset_y;
-    rectangle(
+    r.fill_rect(
         color,
         [
             x,
             y,
             block_size * (width as f64),
             block_size * (height as f64),
-        ],
-        con.transform,
-        g,
+        ]
     );
 }
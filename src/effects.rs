@@ -0,0 +1,195 @@
+use piston_window::types::Color;
+use crate::render::Renderer;
+use crate::rng::GameRng;
+
+use crate::draw::BoardLayout;
+use crate::events::{EventListener, FoodKind, GameEvent};
+use crate::theme::{Palette, Theme};
+
+const EAT_PARTICLES: usize = 10;
+const BIG_EAT_PARTICLES: usize = 24;
+const PARTICLE_LIFETIME: f64 = 0.6;
+const PULSE_LIFETIME: f64 = 0.6;
+const FLASH_TIME: f64 = 0.3;
+const DEATH_ANIMATION_TIME: f64 = 1.0; // 死亡动画时长，播放完再显示 GameOver 菜单
+
+// 粒子位置与速度都以格子为单位，窗口缩放时自动跟随
+#[derive(Clone)]
+struct Particle {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    size: f64,
+    life: f64,
+    max_life: f64,
+    color: Color,
+}
+
+// 大食物出现时向外扩散的方框
+#[derive(Clone)]
+struct Pulse {
+    x: f64, // 中心（格子坐标）
+    y: f64,
+    age: f64,
+    color: Color,
+}
+
+#[derive(Clone)]
+pub struct Effects {
+    particles: Vec<Particle>,
+    pulses: Vec<Pulse>,
+    flash: f64,         // 死亡闪光剩余时间
+    dying: f64,         // 死亡动画剩余时间
+    flash_color: Color,
+    theme: Theme, // 当前配色，粒子颜色随之变化
+    rng: GameRng, // 粒子方向与速度；离屏渲染时固定种子，画面可复现
+}
+
+impl Effects {
+    pub fn new() -> Effects {
+        Effects {
+            particles: Vec::new(),
+            pulses: Vec::new(),
+            flash: 0.0,
+            dying: 0.0,
+            flash_color: [1.0, 1.0, 1.0, 1.0],
+            theme: Theme::from_palette(Palette::Default),
+            rng: GameRng::from_entropy(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let (theme, rng) = (self.theme, self.rng);
+        *self = Effects::new();
+        self.theme = theme;
+        self.rng = rng;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+
+    fn burst(&mut self, cx: f64, cy: f64, count: usize, max_speed: f64, color: Color) {
+        for _ in 0..count {
+            let angle = self.rng.gen_f64(0.0, std::f64::consts::PI * 2.0);
+            let speed = self.rng.gen_f64(max_speed * 0.3, max_speed);
+            self.particles.push(Particle {
+                x: cx,
+                y: cy,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                size: self.rng.gen_f64(0.15, 0.35),
+                life: PARTICLE_LIFETIME,
+                max_life: PARTICLE_LIFETIME,
+                color,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for p in &mut self.particles {
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            // 简单的阻尼，让粒子逐渐减速
+            p.vx *= 1.0 - (3.0 * dt).min(1.0);
+            p.vy *= 1.0 - (3.0 * dt).min(1.0);
+            p.life -= dt;
+        }
+        self.particles.retain(|p| p.life > 0.0);
+        for pulse in &mut self.pulses {
+            pulse.age += dt;
+        }
+        self.pulses.retain(|p| p.age < PULSE_LIFETIME);
+        self.flash = (self.flash - dt).max(0.0);
+        self.dying = (self.dying - dt).max(0.0);
+    }
+
+    // 死亡动画是否还在播放
+    pub fn is_dying(&self) -> bool {
+        self.dying > 0.0
+    }
+
+    pub fn draw(&self, layout: BoardLayout, board_w: i32, board_h: i32, r: &mut dyn Renderer) {
+        let bs = layout.block_size;
+        // 扩散的光环和散开的碎片不画到游戏区域外
+        let (left, top) = layout.to_screen(0.0, 0.0);
+        r.set_clip(Some([left, top, board_w as f64 * bs, board_h as f64 * bs]));
+        for pulse in &self.pulses {
+            let t = pulse.age / PULSE_LIFETIME;
+            let half = 1.0 + t * 1.5; // 从 2x2 扩散到 5x5
+            let alpha = (1.0 - t) as f32;
+            let color = [pulse.color[0], pulse.color[1], pulse.color[2], alpha];
+            let (x, y) = layout.to_screen(pulse.x - half, pulse.y - half);
+            let size = half * 2.0 * bs;
+            let th = bs * 0.15;
+            r.fill_rect(color, [x, y, size, th]);
+            r.fill_rect(color, [x, y + size - th, size, th]);
+            r.fill_rect(color, [x, y, th, size]);
+            r.fill_rect(color, [x + size - th, y, th, size]);
+        }
+        for p in &self.particles {
+            let alpha = (p.life / p.max_life) as f32 * p.color[3];
+            let color = [p.color[0], p.color[1], p.color[2], alpha];
+            let (x, y) = layout.to_screen(p.x - p.size / 2.0, p.y - p.size / 2.0);
+            r.fill_rect(color, [x, y, p.size * bs, p.size * bs]);
+        }
+        if self.flash > 0.0 {
+            let alpha = (self.flash / FLASH_TIME) as f32 * self.flash_color[3];
+            let color = [self.flash_color[0], self.flash_color[1], self.flash_color[2], alpha];
+            r.fill_rect(color, [left, top, board_w as f64 * bs, board_h as f64 * bs]);
+        }
+        r.set_clip(None);
+    }
+}
+
+impl EventListener for Effects {
+    fn on_event(&mut self, event: &GameEvent) {
+        let theme = self.theme;
+        match event {
+            GameEvent::FoodEaten { kind, pos, .. } => {
+                if *kind == FoodKind::Big {
+                    // 大食物占 2x2，中心在左上角格子的右下角
+                    let (cx, cy) = (pos.0 as f64 + 1.0, pos.1 as f64 + 1.0);
+                    self.burst(cx, cy, BIG_EAT_PARTICLES, 8.0, theme.big_food);
+                } else {
+                    let (cx, cy) = (pos.0 as f64 + 0.5, pos.1 as f64 + 0.5);
+                    self.burst(cx, cy, EAT_PARTICLES, 5.0, theme.food);
+                }
+            }
+            GameEvent::BigFoodSpawned { pos } => {
+                self.pulses.push(Pulse {
+                    x: pos.0 as f64 + 1.0,
+                    y: pos.1 as f64 + 1.0,
+                    age: 0.0,
+                    color: theme.big_food,
+                });
+            }
+            GameEvent::Died { segments, .. } => {
+                self.flash = FLASH_TIME;
+                self.dying = DEATH_ANIMATION_TIME;
+                self.flash_color = theme.game_over;
+                // 蛇身每一节向四周散开
+                for &(x, y) in segments {
+                    let angle = self.rng.gen_f64(0.0, std::f64::consts::PI * 2.0);
+                    let speed = self.rng.gen_f64(2.0, 7.0);
+                    self.particles.push(Particle {
+                        x: x as f64 + 0.5,
+                        y: y as f64 + 0.5,
+                        vx: angle.cos() * speed,
+                        vy: angle.sin() * speed,
+                        size: 0.8,
+                        life: DEATH_ANIMATION_TIME,
+                        max_life: DEATH_ANIMATION_TIME,
+                        color: theme.snake,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
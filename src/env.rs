@@ -0,0 +1,732 @@
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Instant;
+
+use crate::ai::{food_cells, food_distance};
+use crate::events::{DeathCause, GameEvent};
+use crate::game::{Game, Level, Tuning};
+use crate::keybindings::{direction_key, DIRECTIONS};
+use crate::menu::{GameMode, GameSpeed};
+use crate::rng::GameRng;
+use crate::sim::SIM_DT;
+use crate::snake::Direction;
+
+pub const GRID_CHANNELS: usize = 4; // 墙、蛇身、蛇头、食物
+pub const FEATURE_COUNT: usize = 11;
+const MAX_UPDATES_PER_STEP: u32 = 100_000; // 防止蛇不再移动时死循环
+
+// 动作相对蛇头当前的方向，因此不会掉头
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Straight,
+    Left,
+    Right,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Straight, Action::Left, Action::Right];
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Action::Straight => "straight",
+            Action::Left => "left",
+            Action::Right => "right",
+        }
+    }
+
+    // 接受序号 0/1/2 或名字
+    pub fn parse(s: &str) -> Option<Action> {
+        match s.parse::<usize>() {
+            Ok(i) => Action::ALL.get(i).copied(),
+            Err(_) => Action::ALL.iter().copied().find(|a| a.key() == s),
+        }
+    }
+
+    pub fn direction(self, current: Direction) -> Direction {
+        match self {
+            Action::Straight => current,
+            Action::Left => current.left(),
+            Action::Right => current.right(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObservationKind {
+    Grid,     // GRID_CHANNELS x 高 x 宽 的 0/1 张量
+    Features, // 危险（直行/左/右）、当前方向、食物方位，共 FEATURE_COUNT 个值
+    Raw,      // 原始状态
+}
+
+impl ObservationKind {
+    pub const ALL: [ObservationKind; 3] = [ObservationKind::Grid, ObservationKind::Features, ObservationKind::Raw];
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            ObservationKind::Grid => "grid",
+            ObservationKind::Features => "features",
+            ObservationKind::Raw => "raw",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ObservationKind> {
+        ObservationKind::ALL.iter().copied().find(|k| k.key() == key)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawState {
+    pub width: i32,
+    pub height: i32,
+    pub direction: Direction,
+    pub segments: Vec<(i32, i32)>, // 蛇头在前
+    pub food: Option<(i32, i32)>,
+    pub big_food: Option<(i32, i32)>, // 左上角，占 2x2
+    pub score: i32,
+    pub remaining_time: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Observation {
+    Grid { width: usize, height: usize, data: Vec<f32> }, // 按 通道、行、列 的顺序排列
+    Features(Vec<f32>),
+    Raw(RawState),
+}
+
+impl Observation {
+    pub fn from_game(game: &Game, kind: ObservationKind) -> Observation {
+        match kind {
+            ObservationKind::Grid => grid(game),
+            ObservationKind::Features => Observation::Features(features(game)),
+            ObservationKind::Raw => Observation::Raw(RawState {
+                width: game.board_size().0,
+                height: game.board_size().1,
+                direction: game.head_direction(),
+                segments: game.snake_segments(),
+                food: game.food_position(),
+                big_food: game.big_food_position(),
+                score: game.get_score(),
+                remaining_time: game.get_remaining_time(),
+            }),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        match self {
+            Observation::Grid { width, height, data } => format!(
+                "{{\"shape\":[{},{},{}],\"data\":[{}]}}",
+                GRID_CHANNELS,
+                height,
+                width,
+                join(data.iter().map(|v| v.to_string()))
+            ),
+            Observation::Features(values) => format!("[{}]", join(values.iter().map(|v| v.to_string()))),
+            Observation::Raw(s) => format!(
+                "{{\"width\":{},\"height\":{},\"direction\":\"{}\",\"segments\":[{}],\"food\":{},\"big_food\":{},\"score\":{},\"remaining_time\":{}}}",
+                s.width,
+                s.height,
+                direction_key(s.direction),
+                join(s.segments.iter().map(|&p| point_json(p))),
+                s.food.map_or("null".to_string(), point_json),
+                s.big_food.map_or("null".to_string(), point_json),
+                s.score,
+                s.remaining_time.map_or("null".to_string(), |t| format!("{:.3}", t))
+            ),
+        }
+    }
+}
+
+fn grid(game: &Game) -> Observation {
+    let (w, h) = game.board_size();
+    let (width, height) = (w as usize, h as usize);
+    let mut data = vec![0.0; GRID_CHANNELS * width * height];
+    let mut set = |channel: usize, (x, y): (i32, i32)| {
+        if x >= 0 && y >= 0 && x < w && y < h {
+            data[(channel * height + y as usize) * width + x as usize] = 1.0;
+        }
+    };
+    for x in 0..w {
+        set(0, (x, 0));
+        set(0, (x, h - 1));
+    }
+    for y in 0..h {
+        set(0, (0, y));
+        set(0, (w - 1, y));
+    }
+    for p in game.snake_segments() {
+        set(1, p);
+    }
+    set(2, game.head_position());
+    for p in food_cells(game) {
+        set(3, p);
+    }
+    Observation::Grid { width, height, data }
+}
+
+fn features(game: &Game) -> Vec<f32> {
+    let flag = |b: bool| if b { 1.0 } else { 0.0 };
+    let current = game.head_direction();
+    let (hx, hy) = game.head_position();
+    let mut values = vec![
+        flag(!game.is_safe(current)),
+        flag(!game.is_safe(current.left())),
+        flag(!game.is_safe(current.right())),
+    ];
+    values.extend(DIRECTIONS.iter().map(|&d| flag(d == current)));
+    // 最近的食物在蛇头的哪一侧（上、下、左、右）
+    let nearest = food_cells(game).into_iter().min_by_key(|&(fx, fy)| (fx - hx).abs() + (fy - hy).abs());
+    let (fx, fy) = nearest.unwrap_or((hx, hy));
+    values.extend([flag(fy < hy), flag(fy > hy), flag(fx < hx), flag(fx > hx)]);
+    values
+}
+
+// 奖励塑形：每一步的奖励是下列各项之和
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardConfig {
+    pub food: f32,     // 每得 1 分（已含连击倍率）
+    pub death: f32,    // 死亡（撞墙、咬到自己、时间耗尽）
+    pub step: f32,     // 每走一格
+    pub win: f32,      // 占满整个游戏区域
+    pub approach: f32, // 每靠近最近的食物一格（远离时为负）
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        RewardConfig {
+            food: 1.0,
+            death: -10.0,
+            step: -0.01,
+            win: 10.0,
+            approach: 0.0,
+        }
+    }
+}
+
+impl RewardConfig {
+    // 按名字修改一项，名字未知或值不是有限数时返回 false
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let v: f32 = match value.parse() {
+            Ok(v) if f32::is_finite(v) => v,
+            _ => return false,
+        };
+        match key {
+            "food" => self.food = v,
+            "death" => self.death = v,
+            "step" => self.step = v,
+            "win" => self.win = v,
+            "approach" => self.approach = v,
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvConfig {
+    pub mode: GameMode,
+    pub speed: GameSpeed,
+    pub level: Level,
+    pub tuning: Tuning,
+    pub observation: ObservationKind,
+    pub reward: RewardConfig,
+    pub max_steps: u64, // 一局最多走的格数，达到后截断
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            mode: GameMode::Classic,
+            speed: GameSpeed::Medium,
+            level: Level::Normal,
+            tuning: Tuning::default(),
+            observation: ObservationKind::Features,
+            reward: RewardConfig::default(),
+            max_steps: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepInfo {
+    pub score: i32,
+    pub length: usize,
+    pub steps: u64, // 本局已走的格数
+    pub death: Option<DeathCause>,
+    pub won: bool,
+    pub truncated: bool, // 因 max_steps 结束而非死亡
+}
+
+impl StepInfo {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"score\":{},\"length\":{},\"steps\":{},\"death\":{},\"won\":{},\"truncated\":{}}}",
+            self.score,
+            self.length,
+            self.steps,
+            self.death.map_or("null".to_string(), |c| format!("\"{}\"", c.label())),
+            self.won,
+            self.truncated
+        )
+    }
+}
+
+// 观察、奖励、是否结束、附加信息
+pub type Transition = (Observation, f32, bool, StepInfo);
+
+// 单个环境。直接驱动真实的 Game，一步就是蛇前进一格
+pub struct Env {
+    config: EnvConfig,
+    game: Game,
+    steps: u64,
+    death: Option<DeathCause>,
+    won: bool,
+    done: bool,
+}
+
+impl Env {
+    pub fn new(config: EnvConfig) -> Env {
+        let game = new_game(&config, 0);
+        Env {
+            config,
+            game,
+            steps: 0,
+            death: None,
+            won: false,
+            done: false,
+        }
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = new_game(&self.config, seed);
+        self.steps = 0;
+        self.death = None;
+        self.won = false;
+        self.done = false;
+        self.observe()
+    }
+
+    pub fn observe(&self) -> Observation {
+        Observation::from_game(&self.game, self.config.observation)
+    }
+
+    // 转向后推进游戏直到蛇前进一格（或本局结束）。本局已结束时奖励为 0，需先 reset
+    pub fn step(&mut self, action: Action) -> Transition {
+        if self.done {
+            return (self.observe(), 0.0, true, self.info(false));
+        }
+        let reward_config = self.config.reward;
+        let had_food = !food_cells(&self.game).is_empty();
+        let before = food_distance(&self.game, self.game.head_position());
+
+        // 和玩家按键一样：转向会立即前进一格，直行则等到下一次移动
+        let mut events = Vec::new();
+        let dir = action.direction(self.game.head_direction());
+        if dir != self.game.head_direction() {
+            self.game.turn(dir);
+            events.extend(self.game.drain_events());
+        }
+        let moved = |events: &[GameEvent]| events.iter().any(|e| matches!(e, GameEvent::TickAdvanced { .. }));
+        let mut updates = 0;
+        while !moved(&events) && !self.game.is_game_over() && updates < MAX_UPDATES_PER_STEP {
+            self.game.update(SIM_DT);
+            events.extend(self.game.drain_events());
+            updates += 1;
+        }
+
+        let mut reward = reward_config.step;
+        let mut ate = false;
+        for event in &events {
+            match event {
+                GameEvent::FoodEaten { points, .. } => {
+                    reward += reward_config.food * *points as f32;
+                    ate = true;
+                }
+                GameEvent::Died { cause, .. } => {
+                    reward += reward_config.death;
+                    self.death = Some(*cause);
+                }
+                GameEvent::Won { .. } => {
+                    reward += reward_config.win;
+                    self.won = true;
+                }
+                _ => {}
+            }
+        }
+        // 吃到食物后新食物的位置与这一步无关，不计靠近奖励
+        if had_food && !ate && !self.game.is_game_over() {
+            let after = food_distance(&self.game, self.game.head_position());
+            reward += reward_config.approach * (before - after) as f32;
+        }
+
+        self.steps += 1;
+        let truncated = !self.game.is_game_over() && self.steps >= self.config.max_steps;
+        self.done = self.game.is_game_over() || truncated;
+        (self.observe(), reward, self.done, self.info(truncated))
+    }
+
+    fn info(&self, truncated: bool) -> StepInfo {
+        StepInfo {
+            score: self.game.get_score(),
+            length: self.game.snake_length(),
+            steps: self.steps,
+            death: self.death,
+            won: self.won,
+            truncated,
+        }
+    }
+}
+
+fn new_game(config: &EnvConfig, seed: u64) -> Game {
+    let (w, h) = config.level.board_size();
+    let mut game = Game::new_seeded(w, h, config.mode, config.speed, seed);
+    game.set_tuning(config.tuning);
+    game
+}
+
+// 多个环境同时执行，按 CPU 核数分组在多个线程上运行。
+// 某个环境结束后立即用新种子重开：返回的观察是新一局的第一帧，info 仍是刚结束的那一局
+pub struct VecEnv {
+    envs: Vec<Env>,
+    seeds: GameRng,
+    threads: usize,
+}
+
+impl VecEnv {
+    pub fn new(config: EnvConfig, count: usize) -> VecEnv {
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        VecEnv {
+            envs: (0..count).map(|_| Env::new(config.clone())).collect(),
+            seeds: GameRng::new(0),
+            threads: cores.min(count).max(1),
+        }
+    }
+
+    pub fn num_envs(&self) -> usize {
+        self.envs.len()
+    }
+
+    // 第 i 个环境的种子由 seed 派生，同一 seed 与同样的动作序列得到同样的结果
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.seeds = GameRng::new(seed);
+        let seeds = &mut self.seeds;
+        self.envs.iter_mut().map(|env| env.reset(seeds.next_u64())).collect()
+    }
+
+    pub fn step(&mut self, actions: &[Action]) -> Vec<Transition> {
+        assert_eq!(actions.len(), self.envs.len(), "每个环境需要一个动作");
+        let mut results: Vec<Transition> = if self.threads <= 1 {
+            self.envs.iter_mut().zip(actions).map(|(env, &a)| env.step(a)).collect()
+        } else {
+            let chunk = self.envs.len().div_ceil(self.threads);
+            thread::scope(|s| {
+                let handles: Vec<_> = self
+                    .envs
+                    .chunks_mut(chunk)
+                    .zip(actions.chunks(chunk))
+                    .map(|(envs, actions)| {
+                        s.spawn(move || envs.iter_mut().zip(actions).map(|(env, &a)| env.step(a)).collect::<Vec<_>>())
+                    })
+                    .collect();
+                handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+            })
+        };
+        // 在主线程上按顺序取种子，结果与线程数无关
+        for (env, result) in self.envs.iter_mut().zip(results.iter_mut()) {
+            if result.2 {
+                result.0 = env.reset(self.seeds.next_u64());
+            }
+        }
+        results
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvOptions {
+    pub envs: usize,
+    pub steps: u64, // 随机动作测试时所有环境合计的步数
+    pub seed: u64,
+    pub serve: bool,
+    pub config: EnvConfig,
+}
+
+impl Default for EnvOptions {
+    fn default() -> Self {
+        EnvOptions {
+            envs: 8,
+            steps: 100_000,
+            seed: 0,
+            serve: false,
+            config: EnvConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvReport {
+    pub envs: usize,
+    pub steps: u64,
+    pub episodes: u64,
+    pub total_return: f64,
+    pub total_score: i64,
+    pub elapsed: f64, // 实际耗时（秒）
+}
+
+impl EnvReport {
+    pub fn print(&self) {
+        let per_episode = |v: f64| v / self.episodes.max(1) as f64;
+        println!("环境数:     {}", self.envs);
+        println!("总步数:     {}", self.steps);
+        println!("实际耗时:   {:.3} 秒", self.elapsed);
+        println!("步/秒:      {:.0}", self.steps as f64 / self.elapsed.max(1e-9));
+        println!("完成局数:   {}", self.episodes);
+        println!("平均回报:   {:.3}", per_episode(self.total_return));
+        println!("平均得分:   {:.1}", per_episode(self.total_score as f64));
+    }
+}
+
+// 用随机动作跑满 steps 步，检查环境能正常运行并测量吞吐
+pub fn run_random(options: &EnvOptions) -> EnvReport {
+    let mut vec_env = VecEnv::new(options.config.clone(), options.envs);
+    let mut rng = GameRng::new(options.seed);
+    let mut returns = vec![0.0f64; vec_env.num_envs()];
+    let mut report = EnvReport {
+        envs: vec_env.num_envs(),
+        steps: 0,
+        episodes: 0,
+        total_return: 0.0,
+        total_score: 0,
+        elapsed: 0.0,
+    };
+    let start = Instant::now();
+    vec_env.reset(options.seed);
+    while report.steps < options.steps {
+        let actions: Vec<Action> = (0..vec_env.num_envs()).map(|_| Action::ALL[rng.gen_range(0, 3) as usize]).collect();
+        for (i, (_, reward, done, info)) in vec_env.step(&actions).into_iter().enumerate() {
+            returns[i] += reward as f64;
+            if done {
+                report.episodes += 1;
+                report.total_return += returns[i];
+                report.total_score += info.score as i64;
+                returns[i] = 0.0;
+            }
+        }
+        report.steps += vec_env.num_envs() as u64;
+    }
+    report.elapsed = start.elapsed().as_secs_f64();
+    report
+}
+
+// 行协议：从标准输入每行读一条命令，向标准输出每行写一个 JSON 对象。
+//   spec                 环境数、动作与观察的形状
+//   reset [种子]         重开所有环境
+//   step <动作> ...      每个环境一个动作（0/1/2 或 straight/left/right）
+//   quit                 退出
+pub fn serve(options: &EnvOptions) -> io::Result<()> {
+    serve_lines(options, io::stdin().lock(), io::stdout().lock())
+}
+
+fn serve_lines<R: BufRead, W: Write>(options: &EnvOptions, input: R, mut out: W) -> io::Result<()> {
+    let mut vec_env = VecEnv::new(options.config.clone(), options.envs);
+    vec_env.reset(options.seed);
+    for line in input.lines() {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        let reply = match parts.next() {
+            None => continue,
+            Some("quit") => break,
+            Some("spec") => spec_json(&vec_env, &options.config),
+            Some("reset") => match parts.next().map(str::parse::<u64>) {
+                None => observations_json(&vec_env.reset(options.seed)),
+                Some(Ok(seed)) => observations_json(&vec_env.reset(seed)),
+                Some(Err(_)) => error_json("seed must be a non-negative integer"),
+            },
+            Some("step") => {
+                let actions: Option<Vec<Action>> = parts.map(Action::parse).collect();
+                match actions {
+                    Some(actions) if actions.len() == vec_env.num_envs() => step_json(&vec_env.step(&actions)),
+                    Some(_) => error_json("expected one action per environment"),
+                    None => error_json("actions are 0/1/2 or straight/left/right"),
+                }
+            }
+            Some(_) => error_json("unknown command"),
+        };
+        writeln!(out, "{}", reply)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn spec_json(vec_env: &VecEnv, config: &EnvConfig) -> String {
+    let (w, h) = config.level.board_size();
+    let shape = match config.observation {
+        ObservationKind::Grid => format!("[{},{},{}]", GRID_CHANNELS, h, w),
+        ObservationKind::Features => format!("[{}]", FEATURE_COUNT),
+        ObservationKind::Raw => "null".to_string(),
+    };
+    format!(
+        "{{\"envs\":{},\"actions\":[{}],\"observation\":\"{}\",\"shape\":{}}}",
+        vec_env.num_envs(),
+        join(Action::ALL.iter().map(|a| format!("\"{}\"", a.key()))),
+        config.observation.key(),
+        shape
+    )
+}
+
+fn observations_json(observations: &[Observation]) -> String {
+    format!("{{\"observations\":[{}]}}", join(observations.iter().map(Observation::to_json)))
+}
+
+fn step_json(results: &[Transition]) -> String {
+    format!(
+        "{{\"observations\":[{}],\"rewards\":[{}],\"dones\":[{}],\"infos\":[{}]}}",
+        join(results.iter().map(|r| r.0.to_json())),
+        join(results.iter().map(|r| r.1.to_string())),
+        join(results.iter().map(|r| r.2.to_string())),
+        join(results.iter().map(|r| r.3.to_json()))
+    )
+}
+
+fn error_json(message: &str) -> String {
+    format!("{{\"error\":\"{}\"}}", message)
+}
+
+fn point_json((x, y): (i32, i32)) -> String {
+    format!("[{},{}]", x, y)
+}
+
+fn join<I: Iterator<Item = String>>(items: I) -> String {
+    items.collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::{SaveReader, SaveWriter};
+
+    // 在新开的一局上摆好蛇和食物（经存档读回，与读档走同一条校验路径）
+    fn placed_game(direction: Direction, body: &[(i32, i32)], food: (i32, i32)) -> Game {
+        let mut w = SaveWriter::new();
+        Game::new_seeded(10, 10, GameMode::Classic, GameSpeed::Medium, 1).save_state(&mut w);
+        w.put("game.direction", direction_key(direction));
+        w.put_points("game.snake", body);
+        w.put("game.tail", "");
+        w.put("game.food", format!("true,{},{}", food.0, food.1));
+        let path = std::env::temp_dir().join(format!("snake-env-test-{}.cfg", std::process::id()));
+        w.write(&path).unwrap();
+        let game = SaveReader::read(&path).and_then(|r| Game::load_state(&r));
+        let _ = std::fs::remove_file(&path);
+        game.unwrap()
+    }
+
+    #[test]
+    fn reset_with_same_seed_is_identical() {
+        for kind in ObservationKind::ALL {
+            let config = EnvConfig { observation: kind, ..EnvConfig::default() };
+            let mut a = Env::new(config.clone());
+            let mut b = Env::new(config);
+            assert_eq!(a.reset(42), b.reset(42));
+            for _ in 0..10 {
+                assert_eq!(a.step(Action::Left), b.step(Action::Left));
+            }
+        }
+    }
+
+    #[test]
+    fn driving_into_wall_ends_episode() {
+        // 开局蛇头在 (4,2) 向右，30 格宽的场地 x=29 是墙：直行 24 步安全，第 25 步撞墙
+        let mut env = Env::new(EnvConfig::default());
+        env.reset(0);
+        for i in 0..24 {
+            let (_, reward, done, info) = env.step(Action::Straight);
+            assert!(!done, "第 {} 步不应结束", i + 1);
+            assert_eq!(reward, -0.01);
+            assert_eq!(info.steps, i + 1);
+        }
+        let (_, reward, done, info) = env.step(Action::Straight);
+        assert!(done);
+        assert_eq!(reward, -0.01 - 10.0);
+        assert_eq!((info.death, info.truncated, info.steps), (Some(DeathCause::Wall), false, 25));
+        // 结束后继续 step 奖励为 0
+        assert_eq!(env.step(Action::Left).1, 0.0);
+    }
+
+    #[test]
+    fn features_for_placed_head() {
+        // 蛇头 (1,3) 朝上：左边是墙，右边是蛇身，前方空着；食物在右上方
+        let game = placed_game(Direction::Up, &[(1, 3), (2, 3), (2, 4)], (5, 1));
+        let expected = [
+            0.0, 1.0, 1.0, // 危险：直行、左、右
+            1.0, 0.0, 0.0, 0.0, // 当前方向：上、下、左、右
+            1.0, 0.0, 0.0, 1.0, // 食物在上、下、左、右
+        ];
+        assert_eq!(features(&game), expected);
+        assert_eq!(expected.len(), FEATURE_COUNT);
+    }
+
+    // 按 VecEnv 的规则逐个执行 Env：先全部 step，再按顺序用派生种子重开已结束的环境
+    fn sequential(config: &EnvConfig, count: usize, seed: u64, actions: &[Vec<Action>]) -> Vec<Vec<Transition>> {
+        let mut seeds = GameRng::new(seed);
+        let mut envs: Vec<Env> = (0..count).map(|_| Env::new(config.clone())).collect();
+        for env in &mut envs {
+            env.reset(seeds.next_u64());
+        }
+        actions
+            .iter()
+            .map(|step| {
+                let mut results: Vec<Transition> = envs.iter_mut().zip(step).map(|(env, &a)| env.step(a)).collect();
+                for (env, result) in envs.iter_mut().zip(results.iter_mut()) {
+                    if result.2 {
+                        result.0 = env.reset(seeds.next_u64());
+                    }
+                }
+                results
+            })
+            .collect()
+    }
+
+    #[test]
+    fn vec_env_matches_sequential_envs() {
+        let config = EnvConfig::default();
+        let count = 5;
+        let mut rng = GameRng::new(9);
+        // 足够多步，让一些环境结束并自动重开
+        let actions: Vec<Vec<Action>> =
+            (0..200).map(|_| (0..count).map(|_| Action::ALL[rng.gen_range(0, 3) as usize]).collect()).collect();
+        let expected = sequential(&config, count, 7, &actions);
+        assert!(expected.iter().flatten().any(|t| t.2));
+        for threads in [1, 2, 3] {
+            let mut vec_env = VecEnv::new(config.clone(), count);
+            vec_env.threads = threads;
+            vec_env.reset(7);
+            let actual: Vec<Vec<Transition>> = actions.iter().map(|a| vec_env.step(a)).collect();
+            assert!(actual == expected, "{} 个线程时结果不同", threads);
+        }
+    }
+
+    #[test]
+    fn serve_round_trip() {
+        let options = EnvOptions { envs: 2, seed: 3, ..EnvOptions::default() };
+        let mut out = Vec::new();
+        serve_lines(&options, "spec\nreset 5\nstep 0 left\nstep 0\nbogus\nquit\nspec\n".as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 5, "quit 之后不再回复");
+        assert_eq!(
+            lines[0],
+            "{\"envs\":2,\"actions\":[\"straight\",\"left\",\"right\"],\"observation\":\"features\",\"shape\":[11]}"
+        );
+
+        let expected = sequential(&options.config, 2, 5, &[vec![Action::Straight, Action::Left]]);
+        let mut envs: Vec<Env> = (0..2).map(|_| Env::new(options.config.clone())).collect();
+        let mut seeds = GameRng::new(5);
+        let first: Vec<Observation> = envs.iter_mut().map(|e| e.reset(seeds.next_u64())).collect();
+        assert_eq!(lines[1], observations_json(&first));
+        assert_eq!(lines[2], step_json(&expected[0]));
+        // 回复中的奖励能按数字读回
+        let rewards = lines[2].split("\"rewards\":[").nth(1).and_then(|r| r.split(']').next()).unwrap();
+        let rewards: Vec<f32> = rewards.split(',').map(|r| r.parse().unwrap()).collect();
+        assert_eq!(rewards, vec![expected[0][0].1, expected[0][1].1]);
+        assert_eq!(lines[3], error_json("expected one action per environment"));
+        assert_eq!(lines[4], error_json("unknown command"));
+    }
+}
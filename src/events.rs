@@ -0,0 +1,77 @@
+use crate::snake::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FoodKind {
+    Normal,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Timeout, // 限时模式时间耗尽
+}
+
+impl DeathCause {
+    pub fn label(&self) -> &'static str {
+        match *self {
+            DeathCause::Wall => "WALL",
+            DeathCause::SelfCollision => "SELF",
+            DeathCause::Timeout => "TIMEOUT",
+        }
+    }
+}
+
+// 游戏逻辑产生的事件。Game 只负责把事件放进队列，
+// 特效、音效、右侧提示等各自订阅处理，不再写进游戏逻辑本身
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    FoodEaten { kind: FoodKind, points: i32, pos: (i32, i32) }, // points 已乘以连击倍率
+    TimeBonus { kind: FoodKind, seconds: i32 }, // 吃到哪种食物得到的加时
+    BigFoodSpawned { pos: (i32, i32) },
+    BigFoodExpired { pos: (i32, i32) },
+    Died { cause: DeathCause, segments: Vec<(i32, i32)> },
+    Won { segments: Vec<(i32, i32)> }, // 蛇占满了整个游戏区域
+    SpeedChanged { multiplier: f64 },
+    TickAdvanced { tick: u64, head: (i32, i32) },
+    Turned { dir: Direction },
+    TimeWarning { seconds_left: i32 }, // 限时模式剩余时间进入最后几秒时每秒一次
+    ComboChanged { combo: u32, multiplier: i32, window: f64 }, // 每次吃到食物后的连击状态，window 为距中断的秒数
+    ComboBroken { combo: u32 },
+    Countdown { seconds_left: u32 }, // 开局或恢复前倒数，每秒一次；0 表示 GO
+}
+
+// 事件订阅者
+pub trait EventListener {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+// 事件队列：游戏逻辑写入，主循环每帧取出后分发
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus { queue: Vec::new() }
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.queue)
+    }
+}
+
+// 按顺序把每个事件交给所有订阅者
+pub fn dispatch(events: &[GameEvent], listeners: &mut [&mut dyn EventListener]) {
+    for event in events {
+        for listener in listeners.iter_mut() {
+            listener.on_event(event);
+        }
+    }
+}
@@ -1,35 +1,137 @@
+use std::collections::HashSet;
+
 use crate::draw::{draw_block_dynamic_with_offset, draw_rectangle_dynamic_with_offset};
 use piston_window::types::Color;
-use piston_window::*;
-use rand::{thread_rng, Rng};
 use crate::snake::{Direction, Snake};
 use crate::menu::{GameMode, GameSpeed};
-const FOOD_COLOR: Color = [0.80, 0.00, 0.00, 1.0];
-const BIG_FOOD_COLOR: Color = [1.0, 0.5, 0.0, 1.0]; // 黄色的大食物
-const BORDER_COLOR: Color = [0.80, 0.00, 0.00, 1.0];
-const GAMEOVER_COLOR: Color = [0.90, 0.00, 0.00, 0.5];
+use crate::draw::BoardLayout;
+use crate::render::Renderer;
+use crate::events::{DeathCause, EventBus, FoodKind, GameEvent};
+use crate::keybindings::{direction_key, parse_direction};
+use crate::rng::GameRng;
+use crate::save::{optional, SaveError, SaveReader, SaveWriter};
+use crate::settings::Settings;
+use crate::theme::Theme;
 const MOVING_PERIOD: f64 = 0.1;
 const RESTART_TIME: f64 = 1.0;
 const TIME_LIMIT_SECONDS: i32 = 30; // 初始30秒
 const TIME_ADD_PER_FOOD: i32 = 10;  // 每吃一个加10秒
 const BIG_FOOD_SPAWN_INTERVAL: f64 = 8.0; // 大食物出现倒计时
 const BIG_FOOD_LIFETIME: f64 = 5.0; //大食物消失倒计时
-const MESSAGE_DISPLAY_TIME: f64 = 5.0; // 信息显示时间
+const TIME_WARNING_SECONDS: i32 = 5; // 限时模式最后几秒开始提示
+const READY_TIME: f64 = 1.0; // 开局倒数前显示 READY 的时间
+const GO_TIME: f64 = 0.6;    // 倒数结束后 GO 的显示时间，此时蛇已经开始移动
+
+// 可调的平衡参数，默认值即上面的常量；批量模拟时可以覆盖以便调参
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    pub time_limit: f64,             // 限时模式初始秒数
+    pub time_add_per_food: i32,      // 限时模式每个普通食物加的秒数（大食物 3 倍）
+    pub big_food_spawn_interval: f64,
+    pub big_food_lifetime: f64,
+    pub speed_ramp: f64,             // 速度模式每得 1 分增加的速度倍数
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            time_limit: TIME_LIMIT_SECONDS as f64,
+            time_add_per_food: TIME_ADD_PER_FOOD,
+            big_food_spawn_interval: BIG_FOOD_SPAWN_INTERVAL,
+            big_food_lifetime: BIG_FOOD_LIFETIME,
+            speed_ramp: 0.1,
+        }
+    }
+}
+
+impl Tuning {
+    // 按名字修改一项；名字未知或值不合法（非正数）时返回 false
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let positive = value.parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0);
+        match (key, positive) {
+            ("time_limit", Some(v)) => self.time_limit = v,
+            ("time_add_per_food", Some(_)) => match value.parse() {
+                Ok(v) => self.time_add_per_food = v,
+                Err(_) => return false,
+            },
+            ("big_food_spawn_interval", Some(v)) => self.big_food_spawn_interval = v,
+            ("big_food_lifetime", Some(v)) => self.big_food_lifetime = v,
+            ("speed_ramp", Some(v)) => self.speed_ramp = v,
+            _ => return false,
+        }
+        true
+    }
+}
+
+// 关卡：决定游戏区域大小（含边框）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Small,
+    Normal,
+    Large,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Small, Level::Normal, Level::Large];
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Level::Small => "small",
+            Level::Normal => "normal",
+            Level::Large => "large",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Level> {
+        Level::ALL.iter().copied().find(|l| l.key() == key)
+    }
+
+    pub fn board_size(&self) -> (i32, i32) {
+        match *self {
+            Level::Small => (20, 20),
+            Level::Normal => (30, 30),
+            Level::Large => (40, 40),
+        }
+    }
+}
 
-struct GameMessage {
-    text: String,
-    lifetime: f64,
+// 开局或恢复时倒数的阶段，供界面绘制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CountdownPhase {
+    Ready,
+    Count(u32),
+    Go,
 }
 
-impl GameMessage {
-    fn new(text: String) -> Self {
-        Self {
-            text,
-            lifetime: MESSAGE_DISPLAY_TIME,
+// 连击规则：在 window 秒内接连吃到食物则连击数加一，
+// 每累计 step 次连击倍率加一，最高 max_multiplier 倍
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComboRules {
+    pub window: f64,
+    pub step: u32,
+    pub max_multiplier: i32,
+}
+
+impl ComboRules {
+    pub fn for_mode(mode: GameMode) -> ComboRules {
+        match mode {
+            GameMode::Classic => ComboRules { window: 3.0, step: 2, max_multiplier: 4 },
+            // 速度模式移动快，窗口更短但上限更高
+            GameMode::Speed => ComboRules { window: 2.0, step: 2, max_multiplier: 5 },
+            // 限时模式本身有加时奖励，倍率涨得慢一些
+            GameMode::Survival => ComboRules { window: 4.0, step: 3, max_multiplier: 3 },
+        }
+    }
+
+    pub fn multiplier(&self, combo: u32) -> i32 {
+        if combo == 0 {
+            return 1;
         }
+        (1 + ((combo - 1) / self.step) as i32).min(self.max_multiplier)
     }
 }
 
+#[derive(Clone)]
 pub struct Game {
     snake: Snake,
     food_exists: bool,
@@ -54,7 +156,17 @@ pub struct Game {
     speed_multiplier: f64,
     speed_setting: GameSpeed,
     remaining_time: Option<f64>, // Survival模式剩余时间，秒
-    messages: Vec<GameMessage>, // 在右侧显示的消息
+    events: EventBus, // 本帧产生、尚未被取走的事件
+    tick: u64, // 蛇已经移动的步数
+    combo_rules: ComboRules,
+    combo: u32,       // 当前连击数
+    combo_timer: f64, // 连击剩余时间，归零后连击中断
+    rng: GameRng,     // 食物位置的随机数，状态随存档保存
+    countdown: f64,         // 剩余倒数时间（含 READY），大于 0 时游戏暂停推进
+    countdown_seconds: u32, // 本次倒数的秒数，超出部分显示 READY
+    go_timer: f64,
+    buffered_dir: Option<Direction>, // 倒数期间按下的方向，倒数结束时生效
+    tuning: Tuning,
 }
 impl Game {
     pub fn new(width: i32, height: i32) -> Game {
@@ -93,7 +205,17 @@ impl Game {
             speed_multiplier,
             speed_setting: speed,
             remaining_time: None,
-            messages: Vec::new(),
+            events: EventBus::new(),
+            tick: 0,
+            combo_rules: ComboRules::for_mode(mode),
+            combo: 0,
+            combo_timer: 0.0,
+            rng: GameRng::from_entropy(),
+            countdown: 0.0,
+            countdown_seconds: 0,
+            go_timer: 0.0,
+            buffered_dir: None,
+            tuning: Tuning::default(),
         };
         if mode == GameMode::Survival {
             g.remaining_time = Some(TIME_LIMIT_SECONDS as f64);
@@ -103,6 +225,21 @@ impl Game {
         g
     }
     
+    // 指定随机数种子：相同种子、相同输入得到完全相同的一局（回放依赖这一点）
+    pub fn new_seeded(width: i32, height: i32, mode: GameMode, speed: GameSpeed, seed: u64) -> Game {
+        let mut g = Self::new_with_mode(width, height, mode, speed);
+        g.rng = GameRng::new(seed);
+        g
+    }
+
+    // 替换平衡参数，需在开局前调用
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+        if self.game_mode == GameMode::Survival {
+            self.remaining_time = Some(tuning.time_limit);
+        }
+    }
+
     pub fn update_window_size(&mut self, new_width: f64, new_height: f64) {
         self.window_width = new_width;
         self.window_height = new_height;
@@ -119,50 +256,59 @@ impl Game {
         self.offset_x = (new_width - game_width) / 2.0;
         self.offset_y = (new_height - game_height) / 2.0;
     }
-    pub fn key_pressed(&mut self, key: Key) {
+    // 玩家要求转向（或沿当前方向加速前进一格）
+    pub fn turn(&mut self, d: Direction) {
         if self.game_over {
             return;
         }
-        let dir = match key {
-            Key::Up => Some(Direction::Up),
-            Key::W => Some(Direction::Up),
-            Key::Down => Some(Direction::Down),
-            Key::S => Some(Direction::Down),
-            Key::Left => Some(Direction::Left),
-            Key::A => Some(Direction::Left),
-            Key::Right => Some(Direction::Right),
-            Key::D => Some(Direction::Right),
-            _ => None,
-        };
-        if let Some(d) = dir {
-            if d == self.snake.head_direction().opposite() {
-                return;
-            }
-            self.update_snake(Some(d));
-        } else {
-            // 非方向键：忽略
+        if d == self.snake.head_direction().opposite() {
+            return;
+        }
+        // 倒数期间只记下最后一次方向，不移动
+        if self.is_counting_down() {
+            self.buffered_dir = Some(d);
+            return;
         }
+        if d != self.snake.head_direction() {
+            self.events.emit(GameEvent::Turned { dir: d });
+        }
+        self.update_snake(Some(d));
     }
-    pub fn draw(&self, con: &Context, g: &mut G2d) {
-        self.snake.draw_dynamic_with_offset(con, g, self.block_size, self.offset_x, self.offset_y);
-        if self.food_exists {
-            draw_block_dynamic_with_offset(FOOD_COLOR, self.food_x, self.food_y, self.block_size, self.offset_x, self.offset_y, con, g);
+
+    // 朝 dir 前进一格是否安全（不撞墙、不咬到自己），供自动驾驶使用
+    pub fn is_safe(&self, dir: Direction) -> bool {
+        self.collision(Some(dir)).is_none()
+    }
+
+    pub fn head_direction(&self) -> Direction {
+        self.snake.head_direction()
+    }
+    pub fn draw(&self, r: &mut dyn Renderer, settings: &Settings) {
+        let theme = Theme::from_palette(settings.palette);
+        // 死亡后蛇身由特效层的散开动画接管
+        if !self.game_over {
+            let progress = if settings.smooth_movement { self.tick_progress() } else { 0.0 };
+            self.snake.draw_styled(settings.snake_style, theme.snake, self.layout(), progress, r);
         }
-        // 绘制大食物（更大的 - 2x2方块）
-        if self.big_food_exists {
-            for dx in 0..2 {
-                for dy in 0..2 {
-                    draw_block_dynamic_with_offset(BIG_FOOD_COLOR, self.big_food_x + dx, self.big_food_y + dy, self.block_size, self.offset_x, self.offset_y, con, g);
-                }
+        if self.food_exists {
+            draw_block_dynamic_with_offset(theme.food, self.food_x, self.food_y, self.block_size, self.offset_x, self.offset_y, r);
+            if settings.shape_cues {
+                self.draw_food_cue(theme.cue, r);
             }
         }
-        draw_rectangle_dynamic_with_offset(BORDER_COLOR, 0, 0, self.width, 1, self.block_size, self.offset_x, self.offset_y, con, g);
-        draw_rectangle_dynamic_with_offset(BORDER_COLOR, 0, self.height - 1, self.width, 1, self.block_size, self.offset_x, self.offset_y, con, g);
-        draw_rectangle_dynamic_with_offset(BORDER_COLOR, 0, 0, 1, self.height, self.block_size, self.offset_x, self.offset_y, con, g);
-        draw_rectangle_dynamic_with_offset(BORDER_COLOR, self.width - 1, 0, 1, self.height, self.block_size, self.offset_x, self.offset_y, con, g);
-        if self.game_over {
-            draw_rectangle_dynamic_with_offset(GAMEOVER_COLOR, 0, 0, self.width, self.height, self.block_size, self.offset_x, self.offset_y, con, g);
+        // 绘制大食物（更大的 - 2x2方块），消失前最后一秒闪烁提示
+        let big_food_blink_off = self.big_food_lifetime > self.tuning.big_food_lifetime - 1.0
+            && ((self.big_food_lifetime * 8.0) as i32) % 2 == 1;
+        if self.big_food_exists && !big_food_blink_off {
+            draw_rectangle_dynamic_with_offset(theme.big_food, self.big_food_x, self.big_food_y, 2, 2, self.block_size, self.offset_x, self.offset_y, r);
+            if settings.shape_cues {
+                self.draw_big_food_cue(theme.cue, r);
+            }
         }
+        draw_rectangle_dynamic_with_offset(theme.border, 0, 0, self.width, 1, self.block_size, self.offset_x, self.offset_y, r);
+        draw_rectangle_dynamic_with_offset(theme.border, 0, self.height - 1, self.width, 1, self.block_size, self.offset_x, self.offset_y, r);
+        draw_rectangle_dynamic_with_offset(theme.border, 0, 0, 1, self.height, self.block_size, self.offset_x, self.offset_y, r);
+        draw_rectangle_dynamic_with_offset(theme.border, self.width - 1, 0, 1, self.height, self.block_size, self.offset_x, self.offset_y, r);
         // 限时模式右上角时间
         if self.game_mode == GameMode::Survival {
             if let Some(sec) = self.remaining_time {
@@ -172,20 +318,53 @@ impl Game {
                 // 右上角
                 let txt_x = self.window_width - 160.0;
                 let txt_y = 40.0;
-                crate::menu::draw_simple_text(&time_str, txt_x, txt_y, 28.0, [1.0, 1.0, 0.0, 1.0], con, g);
+                crate::menu::draw_simple_text(&time_str, txt_x, txt_y, 28.0, [1.0, 1.0, 0.0, 1.0], r);
             }
         }
     }
+    // 普通食物的形状提示：方块中央的十字
+    fn draw_food_cue(&self, color: Color, r: &mut dyn Renderer) {
+        let bs = self.block_size;
+        let x = self.offset_x + self.food_x as f64 * bs;
+        let y = self.offset_y + self.food_y as f64 * bs;
+        r.fill_rect(color, [x + bs * 0.2, y + bs * 0.4, bs * 0.6, bs * 0.2]);
+        r.fill_rect(color, [x + bs * 0.4, y + bs * 0.2, bs * 0.2, bs * 0.6]);
+    }
+
+    // 大食物的形状提示：2x2 区域中央的空心方环
+    fn draw_big_food_cue(&self, color: Color, r: &mut dyn Renderer) {
+        let bs = self.block_size;
+        let x = self.offset_x + self.big_food_x as f64 * bs + bs * 0.4;
+        let y = self.offset_y + self.big_food_y as f64 * bs + bs * 0.4;
+        let size = bs * 1.2;
+        let t = bs * 0.25;
+        r.fill_rect(color, [x, y, size, t]);
+        r.fill_rect(color, [x, y + size - t, size, t]);
+        r.fill_rect(color, [x, y + t, t, size - 2.0 * t]);
+        r.fill_rect(color, [x + size - t, y + t, t, size - 2.0 * t]);
+    }
+
     pub fn update(&mut self, delta_time: f64) {
+        // 倒数期间所有计时（包括限时模式剩余时间）都不推进
+        if self.is_counting_down() {
+            self.update_countdown(delta_time);
+            return;
+        }
+        self.go_timer = (self.go_timer - delta_time).max(0.0);
         self.waiting_time += delta_time;
         if self.game_mode == GameMode::Survival {
             if let Some(rt) = self.remaining_time.as_mut() {
+                let before = rt.ceil() as i32;
                 *rt -= delta_time;
                 if *rt < 0.0 {
                     *rt = 0.0;
                 }
+                let after = rt.ceil() as i32;
+                if after < before && after > 0 && after <= TIME_WARNING_SECONDS {
+                    self.events.emit(GameEvent::TimeWarning { seconds_left: after });
+                }
                 if *rt <= 0.0 && !self.game_over {
-                    self.game_over = true;
+                    self.die(DeathCause::Timeout);
                 }
             }
         }
@@ -198,13 +377,24 @@ impl Game {
         if !self.food_exists {
             self.add_food();
         }
+
+        // 连击计时，超时则中断
+        if self.combo > 0 {
+            self.combo_timer -= delta_time;
+            if self.combo_timer <= 0.0 {
+                self.events.emit(GameEvent::ComboBroken { combo: self.combo });
+                self.combo = 0;
+                self.combo_timer = 0.0;
+            }
+        }
         
         // 处理大食物计时
         self.big_food_timer += delta_time;
         
         // 每8秒生成大食物
-        if !self.big_food_exists && self.big_food_timer >= BIG_FOOD_SPAWN_INTERVAL {
+        if !self.big_food_exists && self.big_food_timer >= self.tuning.big_food_spawn_interval {
             self.add_big_food();
+            self.events.emit(GameEvent::BigFoodSpawned { pos: (self.big_food_x, self.big_food_y) });
             self.big_food_timer = 0.0;
             self.big_food_lifetime = 0.0;
         }
@@ -213,26 +403,92 @@ impl Game {
         if self.big_food_exists {
             self.big_food_lifetime += delta_time;
             // 5秒后移除大食物
-            if self.big_food_lifetime >= BIG_FOOD_LIFETIME {
+            if self.big_food_lifetime >= self.tuning.big_food_lifetime {
                 self.big_food_exists = false;
+                self.events.emit(GameEvent::BigFoodExpired { pos: (self.big_food_x, self.big_food_y) });
             }
         }
         
-        // 更新消息存活时间
-        for msg in &mut self.messages {
-            msg.lifetime -= delta_time;
+        if self.waiting_time > self.moving_period() {
+            self.update_snake(None);
         }
-        self.messages.retain(|msg| msg.lifetime > 0.0);
-        
-        // 根据游戏模式与选择的速度调整移动速度
+    }
+
+    // 开始倒数 seconds 秒；ready 为 true 时先显示 READY（开局时使用）。seconds 为 0 且不显示 READY 时不倒数
+    pub fn start_countdown(&mut self, seconds: u32, ready: bool) {
+        if self.game_over {
+            return;
+        }
+        self.countdown_seconds = seconds;
+        self.countdown = seconds as f64 + if ready && seconds > 0 { READY_TIME } else { 0.0 };
+        self.go_timer = 0.0;
+        self.buffered_dir = None;
+        // 先显示 READY 时，第一个数字在 READY 结束时再提示
+        if seconds > 0 && !ready {
+            self.events.emit(GameEvent::Countdown { seconds_left: seconds });
+        }
+    }
+
+    fn update_countdown(&mut self, delta_time: f64) {
+        let before = self.countdown.ceil() as u32;
+        self.countdown -= delta_time;
+        if self.countdown > 0.0 {
+            let after = self.countdown.ceil() as u32;
+            if after < before && after <= self.countdown_seconds {
+                self.events.emit(GameEvent::Countdown { seconds_left: after });
+            }
+            return;
+        }
+        self.countdown = 0.0;
+        self.go_timer = GO_TIME;
+        self.events.emit(GameEvent::Countdown { seconds_left: 0 });
+        if let Some(d) = self.buffered_dir.take() {
+            if d != self.snake.head_direction() {
+                self.events.emit(GameEvent::Turned { dir: d });
+                self.update_snake(Some(d));
+            }
+        }
+    }
+
+    pub fn is_counting_down(&self) -> bool {
+        self.countdown > 0.0
+    }
+
+    pub fn countdown_phase(&self) -> Option<CountdownPhase> {
+        if self.countdown > self.countdown_seconds as f64 {
+            Some(CountdownPhase::Ready)
+        } else if self.countdown > 0.0 {
+            Some(CountdownPhase::Count(self.countdown.ceil() as u32))
+        } else if self.go_timer > 0.0 && !self.game_over {
+            Some(CountdownPhase::Go)
+        } else {
+            None
+        }
+    }
+
+    // 根据游戏模式与选择的速度计算每移动一格所需的时间
+    fn moving_period(&self) -> f64 {
         let speed_setting_multiplier = match self.speed_setting {
             GameSpeed::Slow => 0.7,    // 慢速
             GameSpeed::Medium => 1.0,  // 中速
             GameSpeed::Fast => 1.5,    // 快速
         };
-        let moving_period = MOVING_PERIOD / (self.speed_multiplier * speed_setting_multiplier);
-        if self.waiting_time > moving_period {
-            self.update_snake(None);
+        MOVING_PERIOD / (self.speed_multiplier * speed_setting_multiplier)
+    }
+
+    // 距离下一次移动的进度（0~1），供平滑绘制插值使用
+    pub fn tick_progress(&self) -> f64 {
+        if self.game_over {
+            return 0.0;
+        }
+        (self.waiting_time / self.moving_period()).clamp(0.0, 1.0)
+    }
+
+    pub fn layout(&self) -> BoardLayout {
+        BoardLayout {
+            block_size: self.block_size,
+            offset_x: self.offset_x,
+            offset_y: self.offset_y,
         }
     }
     fn check_eating(&mut self) {
@@ -240,19 +496,12 @@ impl Game {
         if self.food_exists && self.food_x == head_x && self.food_y == head_y {
             self.food_exists = false;
             self.snake.restore_tail();
-            self.score += 1;
-            self.add_message(format!("+1 Point"));
-            
-            // 在速度模式下，随着分数增加，速度也会增加
-            if self.game_mode == GameMode::Speed {
-                self.speed_multiplier = 1.5 + (self.score as f64 * 0.1);
-            }
-            if self.game_mode == GameMode::Survival {
-                if let Some(rt) = self.remaining_time.as_mut() {
-                    *rt += TIME_ADD_PER_FOOD as f64;
-                    self.add_message(format!("+{} Seconds", TIME_ADD_PER_FOOD));
-                }
-            }
+            let points = self.add_combo(); // 普通食物 1 分
+            self.score += points;
+            self.events.emit(GameEvent::FoodEaten { kind: FoodKind::Normal, points, pos: (head_x, head_y) });
+            self.emit_combo();
+            self.after_eating(FoodKind::Normal, self.tuning.time_add_per_food);
+            self.check_win();
         }
         
         // 检查蛇是否吃到大食物（头部接触到2x2大食物的任意部分）
@@ -265,32 +514,60 @@ impl Game {
                 for _ in 0..3 {
                     self.snake.restore_tail();
                 }
-                self.score += 3;
-                self.add_message(format!("Big Food +3 Points!"));
-                
-                // 在速度模式下，随着分数增加，速度也会增加
-                if self.game_mode == GameMode::Speed {
-                    self.speed_multiplier = 1.5 + (self.score as f64 * 0.1);
-                }
-                if self.game_mode == GameMode::Survival {
-                    if let Some(rt) = self.remaining_time.as_mut() {
-                        let time_bonus = TIME_ADD_PER_FOOD * 3;
-                        *rt += time_bonus as f64;
-                        self.add_message(format!("+{} Seconds", time_bonus));
-                    }
-                }
+                let points = self.add_combo() * 3;
+                self.score += points;
+                self.events.emit(GameEvent::FoodEaten { kind: FoodKind::Big, points, pos: (self.big_food_x, self.big_food_y) });
+                self.emit_combo();
+                self.after_eating(FoodKind::Big, self.tuning.time_add_per_food * 3);
+                self.check_win();
             }
         }
     }
-    fn check_if_snake_alive(&self, dir: Option<Direction>) -> bool {
+
+    // 记一次连击并返回当前得分倍率
+    fn add_combo(&mut self) -> i32 {
+        self.combo += 1;
+        self.combo_timer = self.combo_rules.window;
+        self.combo_rules.multiplier(self.combo)
+    }
+
+    fn emit_combo(&mut self) {
+        self.events.emit(GameEvent::ComboChanged {
+            combo: self.combo,
+            multiplier: self.combo_rules.multiplier(self.combo),
+            window: self.combo_rules.window,
+        });
+    }
+
+    // 吃到食物后的模式相关处理：速度模式加速，限时模式加时间
+    fn after_eating(&mut self, kind: FoodKind, time_bonus: i32) {
+        // 在速度模式下，随着分数增加，速度也会增加
+        if self.game_mode == GameMode::Speed {
+            self.speed_multiplier = 1.5 + (self.score as f64 * self.tuning.speed_ramp);
+            self.events.emit(GameEvent::SpeedChanged { multiplier: self.speed_multiplier });
+        }
+        if self.game_mode == GameMode::Survival {
+            if let Some(rt) = self.remaining_time.as_mut() {
+                *rt += time_bonus as f64;
+                self.events.emit(GameEvent::TimeBonus { kind, seconds: time_bonus });
+            }
+        }
+    }
+
+    // 按给定方向再走一步是否会撞墙或咬到自己
+    fn collision(&self, dir: Option<Direction>) -> Option<DeathCause> {
         let (next_x, next_y) = self.snake.next_head(dir);
         if self.snake.overlap_tail(next_x, next_y) {
-            return false;
+            return Some(DeathCause::SelfCollision);
+        }
+        if next_x > 0 && next_y > 0 && next_x < self.width - 1 && next_y < self.height - 1 {
+            None
+        } else {
+            Some(DeathCause::Wall)
         }
-        next_x > 0 && next_y > 0 && next_x < self.width - 1 && next_y < self.height - 1
     }
     fn add_food(&mut self) {
-        let mut rng = thread_rng();
+        let rng = &mut self.rng;
         let mut new_x = rng.gen_range(1, self.width - 1);
         let mut new_y = rng.gen_range(1, self.width - 1);
         while self.snake.overlap_tail(new_x, new_y) {
@@ -303,7 +580,7 @@ impl Game {
     }
     
     fn add_big_food(&mut self) {
-        let mut rng = thread_rng();
+        let rng = &mut self.rng;
         let mut new_x = rng.gen_range(1, self.width - 2); // -2 确保2x2大小能放得下
         let mut new_y = rng.gen_range(1, self.height - 2);
         
@@ -336,17 +613,34 @@ impl Game {
         self.big_food_exists = true;
     }
     fn update_snake(&mut self, dir: Option<Direction>) {
-        if self.check_if_snake_alive(dir) {
-            self.snake.move_forward(dir);
-            self.check_eating();
-        } else {
-            self.game_over = true;
+        match self.collision(dir) {
+            None => {
+                self.snake.move_forward(dir);
+                self.tick += 1;
+                self.events.emit(GameEvent::TickAdvanced { tick: self.tick, head: self.snake.head_position() });
+                self.check_eating();
+            }
+            Some(cause) => self.die(cause),
         }
         self.waiting_time = 0.0;
     }
+
+    // 蛇占满整个游戏区域即获胜
+    fn check_win(&mut self) {
+        if !self.game_over && self.snake.segments().len() >= self.board_cells() {
+            self.game_over = true;
+            self.events.emit(GameEvent::Won { segments: self.snake.segments() });
+        }
+    }
+
+    fn die(&mut self, cause: DeathCause) {
+        self.game_over = true;
+        self.events.emit(GameEvent::Died { cause, segments: self.snake.segments() });
+    }
     fn restart(&mut self) {
         self.snake = Snake::new(2, 2);
         self.waiting_time = 0.0;
+        self.tick = 0;
         self.food_exists = true;
         self.food_x = 6;
         self.food_y = 4;
@@ -357,6 +651,8 @@ impl Game {
         self.big_food_lifetime = 0.0;
         self.game_over = false;
         self.score = 0;
+        self.combo = 0;
+        self.combo_timer = 0.0;
         // 重置速度倍数
         self.speed_multiplier = match self.game_mode {
             GameMode::Classic => 1.0,
@@ -364,7 +660,7 @@ impl Game {
             GameMode::Survival => 0.8,
         };
         self.remaining_time = if self.game_mode == GameMode::Survival {
-            Some(TIME_LIMIT_SECONDS as f64)
+            Some(self.tuning.time_limit)
         } else {
             None
         };
@@ -373,6 +669,19 @@ impl Game {
     pub fn get_score(&self) -> i32 {
         self.score
     }
+
+    pub fn snake_length(&self) -> usize {
+        self.snake.segments().len()
+    }
+
+    // 可供蛇活动的格子数（不含边框）
+    pub fn board_cells(&self) -> usize {
+        ((self.width - 2) * (self.height - 2)) as usize
+    }
+
+    pub fn speed(&self) -> GameSpeed {
+        self.speed_setting
+    }
     
     pub fn get_game_mode(&self) -> GameMode {
         self.game_mode
@@ -385,62 +694,191 @@ impl Game {
     pub fn set_game_over(&mut self) {
         self.game_over = true;
     }
+    pub fn board_size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    // 取出自上次调用以来产生的所有事件
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain()
+    }
+
     pub fn get_remaining_time(&self) -> Option<f64> {
         self.remaining_time
     }
-    
-    fn add_message(&mut self, text: String) {
-        self.messages.push(GameMessage::new(text));
+
+    pub fn food_position(&self) -> Option<(i32, i32)> {
+        if self.food_exists {
+            Some((self.food_x, self.food_y))
+        } else {
+            None
+        }
     }
-    
-    pub fn draw_messages(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
-        // 在游戏区域右侧绘制消息
-        let start_x = self.offset_x + (self.width as f64 * self.block_size) + 20.0;
-        let start_y = 100.0; // 从分数下方开始
-        let line_height = 30.0;
-        
-        for (i, msg) in self.messages.iter().enumerate() {
-            // 根据剩余存活时间计算透明度（最后0.5秒淡出）
-            let fade_start = 0.5;
-            let alpha = if msg.lifetime > fade_start {
-                1.0
-            } else {
-                (msg.lifetime / fade_start).max(0.0)
-            };
-            
-            let y = start_y + (i as f64 * line_height);
-            let color = if msg.text.contains("Big Food") {
-                // 大食物消息使用橙色
-                [1.0f32, 0.6f32, 0.0f32, alpha as f32]
-            } else {
-                // 普通食物消息使用绿色
-                [0.2f32, 1.0f32, 0.2f32, alpha as f32]
-            };
-            
-            // 绘制背景矩形以提高可见性
-            let text_size = 20.0;
-            let bg_width = 200.0;
-            let bg_height = 28.0;
-            rectangle(
-                [0.0f32, 0.0f32, 0.0f32, (alpha * 0.5) as f32],
-                [start_x, y - bg_height / 2.0, bg_width, bg_height],
-                con.transform,
-                g,
-            );
-            
-            // 绘制消息文本
-            self.draw_text_with_alpha(&msg.text, start_x + bg_width / 2.0, y, text_size, color, con, g, glyphs);
+
+    pub fn head_position(&self) -> (i32, i32) {
+        self.snake.head_position()
+    }
+
+    // 大食物左上角的位置（占 2x2）
+    pub fn big_food_position(&self) -> Option<(i32, i32)> {
+        if self.big_food_exists {
+            Some((self.big_food_x, self.big_food_y))
+        } else {
+            None
         }
     }
-    
-    fn draw_text_with_alpha(&self, text: &str, x: f64, y: f64, size_px: f64, color: [f32; 4], con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
-        use piston_window::character::CharacterCache;
-        
-        let spx = size_px as u32;
-        let total_w = glyphs.width(spx, text).unwrap_or(0.0);
-        let baseline_adjust = size_px * 0.35;
-        let transform = con.transform.trans(x - total_w / 2.0, y + baseline_adjust);
-        let txt = piston_window::Text::new_color(color, spx);
-        let _ = txt.draw(text, glyphs, &con.draw_state, transform, g);
+
+    // 蛇身各节坐标，蛇头在前
+    pub fn snake_segments(&self) -> Vec<(i32, i32)> {
+        self.snake.segments()
+    }
+
+    // 把对局状态写入存档（窗口尺寸与未取走的事件不保存）
+    pub fn save_state(&self, w: &mut SaveWriter) {
+        w.put("game.mode", self.game_mode.key());
+        w.put("game.speed", self.speed_setting.key());
+        w.put("game.width", self.width);
+        w.put("game.height", self.height);
+        w.put("game.direction", direction_key(self.snake.head_direction()));
+        w.put_points("game.snake", &self.snake.segments());
+        w.put("game.tail", optional(self.snake.tail_position().map(|(x, y)| format!("{},{}", x, y))));
+        w.put("game.food", format!("{},{},{}", self.food_exists, self.food_x, self.food_y));
+        w.put("game.big_food", format!("{},{},{}", self.big_food_exists, self.big_food_x, self.big_food_y));
+        w.put("game.big_food_timer", self.big_food_timer);
+        w.put("game.big_food_lifetime", self.big_food_lifetime);
+        w.put("game.waiting_time", self.waiting_time);
+        w.put("game.score", self.score);
+        w.put("game.speed_multiplier", self.speed_multiplier);
+        w.put("game.remaining_time", optional(self.remaining_time));
+        w.put("game.tick", self.tick);
+        w.put("game.combo", self.combo);
+        w.put("game.combo_timer", self.combo_timer);
+        w.put("game.rng", self.rng.state());
+    }
+
+    // 从存档恢复对局；任何字段缺失或超出范围都视为存档损坏
+    pub fn load_state(r: &SaveReader) -> Result<Game, SaveError> {
+        let invalid = |key: &str| SaveError::Invalid(key.to_string());
+        let mode = GameMode::from_key(&r.get::<String>("game.mode")?).ok_or_else(|| invalid("game.mode"))?;
+        let speed = GameSpeed::from_key(&r.get::<String>("game.speed")?).ok_or_else(|| invalid("game.speed"))?;
+        let width: i32 = r.get("game.width")?;
+        let height: i32 = r.get("game.height")?;
+        if !(4..=200).contains(&width) || !(4..=200).contains(&height) {
+            return Err(invalid("game.width"));
+        }
+        let inside = |(x, y): (i32, i32)| x >= 1 && x < width - 1 && y >= 1 && y < height - 1;
+
+        let direction = parse_direction(&r.get::<String>("game.direction")?).ok_or_else(|| invalid("game.direction"))?;
+        let body = r.get_points("game.snake")?;
+        let distance = |a: (i32, i32), b: (i32, i32)| (a.0 - b.0).abs() + (a.1 - b.1).abs();
+        // 吃到食物后尾巴会重复几节，直到蛇走出来；重复只能出现在末尾，其余各节互不重叠
+        let grown = body.windows(2).rev().take_while(|w| w[0] == w[1]).count();
+        let cells = &body[..body.len() - grown];
+        let distinct: HashSet<(i32, i32)> = cells.iter().copied().collect();
+        if body.is_empty()
+            || !body.iter().all(|p| inside(*p))
+            || !cells.windows(2).all(|w| distance(w[0], w[1]) == 1)
+            || distinct.len() != cells.len()
+        {
+            return Err(invalid("game.snake"));
+        }
+        let tail = match r.get_optional::<String>("game.tail")? {
+            Some(raw) => Some(parse_point(&raw).ok_or_else(|| invalid("game.tail"))?),
+            None => None,
+        };
+        if let Some(t) = tail {
+            if !inside(t) || distance(t, body[body.len() - 1]) > 1 {
+                return Err(invalid("game.tail"));
+            }
+        }
+
+        let mut g = Game::new_with_mode(width, height, mode, speed);
+        g.snake = Snake::from_parts(direction, &body, tail);
+        let (exists, x, y) = parse_food(&r.get::<String>("game.food")?).ok_or_else(|| invalid("game.food"))?;
+        if exists && !inside((x, y)) {
+            return Err(invalid("game.food"));
+        }
+        g.food_exists = exists;
+        g.food_x = x;
+        g.food_y = y;
+        let (exists, x, y) = parse_food(&r.get::<String>("game.big_food")?).ok_or_else(|| invalid("game.big_food"))?;
+        if exists && !(inside((x, y)) && inside((x + 1, y + 1))) {
+            return Err(invalid("game.big_food"));
+        }
+        g.big_food_exists = exists;
+        g.big_food_x = x;
+        g.big_food_y = y;
+
+        g.big_food_timer = finite(r, "game.big_food_timer")?;
+        g.big_food_lifetime = finite(r, "game.big_food_lifetime")?;
+        g.waiting_time = finite(r, "game.waiting_time")?;
+        g.score = r.get("game.score")?;
+        g.speed_multiplier = finite(r, "game.speed_multiplier")?;
+        if g.speed_multiplier <= 0.0 {
+            return Err(invalid("game.speed_multiplier"));
+        }
+        g.remaining_time = r.get_optional::<f64>("game.remaining_time")?;
+        if g.remaining_time.is_some() != (mode == GameMode::Survival) || g.remaining_time.is_some_and(|t| !t.is_finite()) {
+            return Err(invalid("game.remaining_time"));
+        }
+        g.tick = r.get("game.tick")?;
+        g.combo = r.get("game.combo")?;
+        g.combo_timer = finite(r, "game.combo_timer")?;
+        g.rng = GameRng::from_state(r.get("game.rng")?).ok_or_else(|| invalid("game.rng"))?;
+        Ok(g)
+    }
+}
+
+fn parse_point(raw: &str) -> Option<(i32, i32)> {
+    let (x, y) = raw.split_once(',')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+// 食物字段格式：是否存在,x,y
+fn parse_food(raw: &str) -> Option<(bool, i32, i32)> {
+    let (exists, point) = raw.split_once(',')?;
+    let (x, y) = parse_point(point)?;
+    Some((exists.parse().ok()?, x, y))
+}
+
+fn finite(r: &SaveReader, key: &str) -> Result<f64, SaveError> {
+    let value: f64 = r.get(key)?;
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(SaveError::Invalid(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(body: &[(i32, i32)], tail: Option<(i32, i32)>) -> Result<Game, SaveError> {
+        let mut game = Game::new_seeded(20, 20, GameMode::Classic, GameSpeed::Medium, 1);
+        game.snake = Snake::from_parts(Direction::Right, body, tail);
+        let mut w = SaveWriter::new();
+        game.save_state(&mut w);
+        let path = std::env::temp_dir().join(format!("snake-save-test-{}-{}.cfg", std::process::id(), body.len()));
+        w.write(&path).unwrap();
+        let loaded = SaveReader::read(&path).and_then(|r| Game::load_state(&r));
+        let _ = std::fs::remove_file(&path);
+        loaded
+    }
+
+    #[test]
+    fn save_right_after_big_food_loads() {
+        // 吃到大食物后尾巴重复三节
+        let body = [(6, 5), (5, 5), (4, 5), (4, 5), (4, 5), (4, 5)];
+        let game = round_trip(&body, Some((4, 5))).unwrap();
+        assert_eq!(game.snake.segments(), body.to_vec());
+    }
+
+    #[test]
+    fn save_with_bad_body_is_rejected() {
+        // 中间重复、身体自相交、尾巴离得太远
+        assert!(round_trip(&[(6, 5), (6, 5), (5, 5)], None).is_err());
+        assert!(round_trip(&[(5, 5), (6, 5), (6, 6), (5, 6), (5, 5)], None).is_err());
+        assert!(round_trip(&[(6, 5), (5, 5)], Some((9, 9))).is_err());
     }
 }
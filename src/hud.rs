@@ -0,0 +1,450 @@
+use piston_window::types::Color;
+
+use crate::draw::BoardLayout;
+use crate::render::Renderer;
+use crate::events::{EventListener, FoodKind, GameEvent};
+use crate::save::{SaveError, SaveReader, SaveWriter};
+use crate::settings::Settings;
+use crate::theme::{Palette, Theme};
+
+const PANEL_WIDTH: f64 = 220.0;
+const LINE_HEIGHT: f64 = 30.0;
+const BG_HEIGHT: f64 = 28.0;
+const FADE_TIME: f64 = 0.5; // 最后0.5秒淡出
+const MAX_NOTIFICATIONS: usize = 6;
+const POPUP_LIFETIME: f64 = 1.0;
+const POPUP_RISE_SPEED: f64 = 1.5; // 每秒上升的格数
+const COMBO_BAR_WIDTH: f64 = 160.0;
+const ACHIEVEMENT_COLOR: Color = [1.0, 0.85, 0.2, 1.0]; // 成就提示统一用金色
+
+// 提示的类别，决定颜色、图标与优先级
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationCategory {
+    Score,
+    BigScore,
+    TimeBonus,
+    BigTimeBonus,
+    Achievement,
+}
+
+// 提示左侧的小图标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationIcon {
+    Food,
+    BigFood,
+    Clock,
+    Star,
+}
+
+impl NotificationCategory {
+    pub fn color(&self, theme: &Theme) -> Color {
+        match *self {
+            NotificationCategory::Score | NotificationCategory::TimeBonus => theme.message_normal,
+            NotificationCategory::BigScore | NotificationCategory::BigTimeBonus => theme.message_big,
+            NotificationCategory::Achievement => ACHIEVEMENT_COLOR,
+        }
+    }
+
+    pub fn icon(&self) -> NotificationIcon {
+        match *self {
+            NotificationCategory::Score => NotificationIcon::Food,
+            NotificationCategory::BigScore => NotificationIcon::BigFood,
+            NotificationCategory::TimeBonus | NotificationCategory::BigTimeBonus => NotificationIcon::Clock,
+            NotificationCategory::Achievement => NotificationIcon::Star,
+        }
+    }
+
+    pub const ALL: [NotificationCategory; 5] = [
+        NotificationCategory::Score,
+        NotificationCategory::BigScore,
+        NotificationCategory::TimeBonus,
+        NotificationCategory::BigTimeBonus,
+        NotificationCategory::Achievement,
+    ];
+
+    // 存档中使用的名字
+    pub fn key(&self) -> &'static str {
+        match *self {
+            NotificationCategory::Score => "score",
+            NotificationCategory::BigScore => "big_score",
+            NotificationCategory::TimeBonus => "time_bonus",
+            NotificationCategory::BigTimeBonus => "big_time_bonus",
+            NotificationCategory::Achievement => "achievement",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<NotificationCategory> {
+        NotificationCategory::ALL.iter().copied().find(|c| c.key() == key)
+    }
+
+    // 数值越大越重要；提示过多时先丢弃优先级低的
+    pub fn priority(&self) -> u8 {
+        match *self {
+            NotificationCategory::Score | NotificationCategory::TimeBonus => 1,
+            NotificationCategory::BigScore | NotificationCategory::BigTimeBonus => 2,
+            NotificationCategory::Achievement => 3,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Notification {
+    pub category: NotificationCategory,
+    pub text: String,
+    pub count: u32, // 相同提示合并后的次数，显示为 "x3"
+    pub lifetime: f64,
+    seq: u64, // 创建或最后一次合并的顺序，用于同优先级内排序
+}
+
+impl Notification {
+    pub fn display_text(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+// 吃到食物时在食物位置弹出并向上飘的文字。位置以格子为单位，
+// 绘制时再换算成屏幕坐标，窗口缩放时随游戏区域一起移动
+#[derive(Clone)]
+struct Popup {
+    text: String,
+    x: f64,
+    y: f64,
+    age: f64,
+    color: Color,
+    scale: f64, // 相对格子大小的字号
+}
+
+// 游戏区域旁的提示列表与食物位置的弹出文字，根据游戏事件生成
+#[derive(Clone)]
+pub struct Hud {
+    notifications: Vec<Notification>,
+    popups: Vec<Popup>,
+    last_eat_pos: (f64, f64), // 上一次进食的位置（格子坐标），连击文字弹在其上方
+    combo: u32,
+    multiplier: i32,
+    combo_window: f64, // 连击窗口总时长
+    combo_left: f64,   // 连击剩余时间，用于绘制逐渐缩短的进度条
+    duration: f64, // 每条提示显示的秒数
+    palette: Palette,
+    next_seq: u64,
+    window_width: f64,
+    window_height: f64,
+}
+
+impl Hud {
+    pub fn new(window_width: f64, window_height: f64) -> Hud {
+        Hud {
+            notifications: Vec::new(),
+            popups: Vec::new(),
+            last_eat_pos: (0.0, 0.0),
+            combo: 0,
+            multiplier: 1,
+            combo_window: 1.0,
+            combo_left: 0.0,
+            duration: Settings::default().notification_duration,
+            palette: Palette::Default,
+            next_seq: 0,
+            window_width,
+            window_height,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+        self.popups.clear();
+        self.combo = 0;
+        self.multiplier = 1;
+        self.combo_left = 0.0;
+    }
+
+    // 保存右侧提示与连击条；飘字很快消失，不保存
+    pub fn save_state(&self, w: &mut SaveWriter) {
+        for (i, n) in self.notifications.iter().enumerate() {
+            w.put(&format!("hud.notification.{:02}", i), format!("{},{},{},{}", n.category.key(), n.count, n.lifetime, n.text));
+        }
+        w.put("hud.combo", format!("{},{},{},{}", self.combo, self.multiplier, self.combo_window, self.combo_left));
+    }
+
+    // 先完整解析再替换，存档损坏时保持原状
+    pub fn load_state(&mut self, r: &SaveReader) -> Result<(), SaveError> {
+        let mut notifications = Vec::new();
+        for key in r.keys_with_prefix("hud.notification.") {
+            let raw: String = r.get(&key)?;
+            let mut parts = raw.splitn(4, ',');
+            let category = parts.next().and_then(NotificationCategory::from_key);
+            let count = parts.next().and_then(|c| c.parse::<u32>().ok());
+            let lifetime = parts.next().and_then(|l| l.parse::<f64>().ok()).filter(|l| l.is_finite());
+            match (category, count, lifetime, parts.next()) {
+                (Some(category), Some(count), Some(lifetime), Some(text)) => notifications.push(Notification {
+                    category,
+                    text: text.to_string(),
+                    count,
+                    lifetime,
+                    seq: notifications.len() as u64,
+                }),
+                _ => return Err(SaveError::Invalid(key)),
+            }
+        }
+        let raw: String = r.get("hud.combo")?;
+        let combo: Vec<&str> = raw.split(',').collect();
+        let parsed = match combo.as_slice() {
+            [c, m, w, l] => match (c.parse(), m.parse(), w.parse::<f64>(), l.parse::<f64>()) {
+                (Ok(c), Ok(m), Ok(w), Ok(l)) if w.is_finite() && l.is_finite() => Some((c, m, w, l)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let (combo, multiplier, window, left) = parsed.ok_or_else(|| SaveError::Invalid("hud.combo".to_string()))?;
+        self.clear();
+        self.next_seq = notifications.len() as u64;
+        self.notifications = notifications;
+        self.combo = combo;
+        self.multiplier = multiplier;
+        self.combo_window = window;
+        self.combo_left = left;
+        Ok(())
+    }
+
+    pub fn update_window_size(&mut self, width: f64, height: f64) {
+        self.window_width = width;
+        self.window_height = height;
+    }
+
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.duration = settings.notification_duration;
+        self.palette = settings.palette;
+    }
+
+    // 新增提示；与仍在显示的同类同文字提示合并计数
+    pub fn notify(&mut self, category: NotificationCategory, text: String) {
+        self.next_seq += 1;
+        let seq = self.next_seq;
+        if let Some(n) = self
+            .notifications
+            .iter_mut()
+            .find(|n| n.category == category && n.text == text)
+        {
+            n.count += 1;
+            n.lifetime = self.duration;
+            n.seq = seq;
+            return;
+        }
+        self.notifications.push(Notification {
+            category,
+            text,
+            count: 1,
+            lifetime: self.duration,
+            seq,
+        });
+        // 超出上限时丢弃优先级最低、最旧的提示
+        while self.notifications.len() > MAX_NOTIFICATIONS {
+            let victim = self
+                .notifications
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, n)| (n.category.priority(), n.seq))
+                .map(|(i, _)| i)
+                .unwrap();
+            self.notifications.remove(victim);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f64) {
+        for n in &mut self.notifications {
+            n.lifetime -= delta_time;
+        }
+        self.notifications.retain(|n| n.lifetime > 0.0);
+        for p in &mut self.popups {
+            p.age += delta_time;
+            p.y -= POPUP_RISE_SPEED * delta_time;
+        }
+        self.popups.retain(|p| p.age < POPUP_LIFETIME);
+        self.combo_left = (self.combo_left - delta_time).max(0.0);
+    }
+
+    // 在格子坐标 (x, y) 处弹出文字
+    fn spawn_popup(&mut self, text: String, x: f64, y: f64, color: Color, scale: f64) {
+        self.popups.push(Popup { text, x, y, age: 0.0, color, scale });
+    }
+
+    // 左上角分数下方的连击数与逐渐缩短的剩余时间条
+    fn draw_combo(&self, theme: &Theme, r: &mut dyn Renderer) {
+        if self.combo < 2 || self.combo_left <= 0.0 {
+            return;
+        }
+        let (x, y) = (20.0, 60.0);
+        let text = format!("COMBO {}  x{}", self.combo, self.multiplier);
+        draw_text_with_alpha(&text, [x + COMBO_BAR_WIDTH / 2.0, y], 18.0, theme.message_big, r);
+        let fraction = (self.combo_left / self.combo_window).clamp(0.0, 1.0);
+        r.fill_rect([0.0, 0.0, 0.0, 0.5], [x, y + 14.0, COMBO_BAR_WIDTH, 8.0]);
+        r.fill_rect(theme.message_big, [x, y + 14.0, COMBO_BAR_WIDTH * fraction, 8.0]);
+    }
+
+    fn draw_popups(&self, layout: BoardLayout, r: &mut dyn Renderer) {
+        for p in &self.popups {
+            // 后半段淡出
+            let t = p.age / POPUP_LIFETIME;
+            let alpha = if t < 0.5 { 1.0 } else { (2.0 - 2.0 * t).max(0.0) } as f32;
+            let color = [p.color[0], p.color[1], p.color[2], alpha];
+            let (x, y) = layout.to_screen(p.x, p.y);
+            let size = (layout.block_size * p.scale).clamp(12.0, 32.0).round();
+            draw_text_with_alpha(&p.text, [x, y], size, color, r);
+        }
+    }
+
+    // 提示面板的位置：优先放在游戏区域右侧，右侧放不下时放左侧，都放不下时叠在窗口右上角
+    fn panel_origin(&self, layout: BoardLayout, board_width: i32) -> (f64, f64) {
+        let board_right = layout.offset_x + board_width as f64 * layout.block_size;
+        if self.window_width - board_right >= PANEL_WIDTH + 40.0 {
+            (board_right + 20.0, 100.0)
+        } else if layout.offset_x >= PANEL_WIDTH + 40.0 {
+            (layout.offset_x - PANEL_WIDTH - 20.0, 100.0)
+        } else {
+            // 让出右上角的菜单按钮与时间
+            ((self.window_width - PANEL_WIDTH - 20.0).max(10.0), 80.0)
+        }
+    }
+
+    pub fn draw(&self, layout: BoardLayout, board_width: i32, r: &mut dyn Renderer, settings: &Settings) {
+        let theme = Theme::from_palette(settings.palette);
+        self.draw_popups(layout, r);
+        self.draw_combo(&theme, r);
+
+        let (start_x, start_y) = self.panel_origin(layout, board_width);
+        // 窗口太矮时只显示放得下的条数
+        let fit = ((self.window_height - start_y) / LINE_HEIGHT).max(0.0) as usize;
+
+        // 高优先级在上，同优先级新的在上
+        let mut order: Vec<&Notification> = self.notifications.iter().collect();
+        order.sort_by_key(|n| std::cmp::Reverse((n.category.priority(), n.seq)));
+
+        for (i, n) in order.iter().take(fit).enumerate() {
+            let alpha = if n.lifetime > FADE_TIME {
+                1.0
+            } else {
+                (n.lifetime / FADE_TIME).max(0.0)
+            } as f32;
+            let y = start_y + i as f64 * LINE_HEIGHT;
+            let base = n.category.color(&theme);
+            let color = [base[0], base[1], base[2], alpha];
+
+            // 绘制背景矩形以提高可见性
+            r.fill_rect(
+                [0.0, 0.0, 0.0, alpha * 0.5],
+                [start_x, y - BG_HEIGHT / 2.0, PANEL_WIDTH, BG_HEIGHT],
+            );
+            draw_icon(n.category.icon(), [start_x + 16.0, y], color, r);
+            // 较长的文字（如成就名）用小一号字，避免超出背景
+            let text = n.display_text();
+            let size = if text.chars().count() > 16 { 16.0 } else { 20.0 };
+            draw_text_with_alpha(&text, [start_x + 16.0 + PANEL_WIDTH / 2.0, y], size, color, r);
+        }
+    }
+}
+
+impl EventListener for Hud {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::FoodEaten { kind: FoodKind::Normal, points, pos } => {
+                self.notify(NotificationCategory::Score, format!("+{} Point", points));
+                let theme = Theme::from_palette(self.palette);
+                let (x, y) = (pos.0 as f64 + 0.5, pos.1 as f64 + 0.5);
+                self.last_eat_pos = (x, y);
+                self.spawn_popup(format!("+{}", points), x, y, theme.message_normal, 0.9);
+            }
+            GameEvent::FoodEaten { kind: FoodKind::Big, points, pos } => {
+                self.notify(NotificationCategory::BigScore, format!("Big Food +{} Points!", points));
+                // 大食物占 2x2，弹出位置取其中心
+                let theme = Theme::from_palette(self.palette);
+                let (x, y) = (pos.0 as f64 + 1.0, pos.1 as f64 + 1.0);
+                self.last_eat_pos = (x, y);
+                self.spawn_popup(format!("+{}", points), x, y, theme.message_big, 0.9);
+            }
+            GameEvent::ComboChanged { combo, multiplier, window } => {
+                self.combo = *combo;
+                self.multiplier = *multiplier;
+                self.combo_window = *window;
+                self.combo_left = *window;
+                if *combo > 1 {
+                    let theme = Theme::from_palette(self.palette);
+                    let (x, y) = self.last_eat_pos;
+                    self.spawn_popup(format!("COMBO x{}", combo), x, y - 1.0, theme.message_big, 0.8);
+                }
+            }
+            GameEvent::ComboBroken { .. } => {
+                self.combo = 0;
+                self.multiplier = 1;
+                self.combo_left = 0.0;
+            }
+            GameEvent::TimeBonus { kind, seconds } => {
+                // 吃大食物获得的加时与普通加时区分显示
+                let category = if *kind == FoodKind::Big {
+                    NotificationCategory::BigTimeBonus
+                } else {
+                    NotificationCategory::TimeBonus
+                };
+                self.notify(category, format!("+{} Seconds", seconds));
+            }
+            _ => {}
+        }
+    }
+}
+
+// 以 center 为中心、用矩形拼出的小图标
+fn draw_icon(icon: NotificationIcon, center: [f64; 2], color: Color, r: &mut dyn Renderer) {
+    let [cx, cy] = center;
+    match icon {
+        NotificationIcon::Food => {
+            r.fill_rect(color, [cx - 5.0, cy - 5.0, 10.0, 10.0]);
+        }
+        NotificationIcon::BigFood => {
+            for &(dx, dy) in &[(-7.0, -7.0), (1.0, -7.0), (-7.0, 1.0), (1.0, 1.0)] {
+                r.fill_rect(color, [cx + dx, cy + dy, 6.0, 6.0]);
+            }
+        }
+        NotificationIcon::Clock => {
+            // 空心表盘 + 两根指针
+            r.fill_rect(color, [cx - 7.0, cy - 7.0, 14.0, 2.0]);
+            r.fill_rect(color, [cx - 7.0, cy + 5.0, 14.0, 2.0]);
+            r.fill_rect(color, [cx - 7.0, cy - 7.0, 2.0, 14.0]);
+            r.fill_rect(color, [cx + 5.0, cy - 7.0, 2.0, 14.0]);
+            r.fill_rect(color, [cx - 1.0, cy - 4.0, 2.0, 5.0]);
+            r.fill_rect(color, [cx - 1.0, cy - 1.0, 4.0, 2.0]);
+        }
+        NotificationIcon::Star => {
+            // 十字加斜向小块拼出的星形
+            r.fill_rect(color, [cx - 2.0, cy - 8.0, 4.0, 16.0]);
+            r.fill_rect(color, [cx - 8.0, cy - 2.0, 16.0, 4.0]);
+            for &(dx, dy) in &[(-5.0, -5.0), (3.0, -5.0), (-5.0, 3.0), (3.0, 3.0)] {
+                r.fill_rect(color, [cx + dx, cy + dy, 2.0, 2.0]);
+            }
+        }
+    }
+}
+
+// 以 pos 为中心绘制文字
+fn draw_text_with_alpha(text: &str, pos: [f64; 2], size_px: f64, color: [f32; 4], r: &mut dyn Renderer) {
+    let spx = size_px as u32;
+    let total_w = r.text_width(text, spx);
+    let baseline_adjust = size_px * 0.35;
+    r.draw_text(text, spx, color, [pos[0] - total_w / 2.0, pos[1] + baseline_adjust]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_bonus_category_follows_food_kind() {
+        let mut hud = Hud::new(800.0, 600.0);
+        // 普通食物的加时调到 15 秒也不算大食物
+        hud.on_event(&GameEvent::TimeBonus { kind: FoodKind::Normal, seconds: 15 });
+        hud.on_event(&GameEvent::TimeBonus { kind: FoodKind::Big, seconds: 6 });
+        let categories: Vec<NotificationCategory> = hud.notifications.iter().map(|n| n.category).collect();
+        assert_eq!(categories, vec![NotificationCategory::TimeBonus, NotificationCategory::BigTimeBonus]);
+    }
+}
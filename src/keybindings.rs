@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use piston_window::Key;
+
+use crate::snake::Direction;
+
+pub const KEYS_FILE: &str = "keys.cfg";
+
+// 与 primary / secondary 数组下标对应的方向
+pub const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+// 方向键绑定：每个方向一个主按键（可在设置中修改）和一个备用按键
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub primary: [Key; 4],
+    pub secondary: [Key; 4],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            primary: [Key::W, Key::S, Key::A, Key::D],
+            secondary: [Key::Up, Key::Down, Key::Left, Key::Right],
+        }
+    }
+}
+
+impl KeyBindings {
+    // 读取按键文件，格式为 up=W,Up；无法识别的项保持默认
+    pub fn load<P: AsRef<Path>>(path: P) -> KeyBindings {
+        let mut bindings = KeyBindings::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return bindings,
+        };
+        for line in content.lines() {
+            let (name, value) = match line.trim().split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            let i = match DIRECTIONS.iter().position(|d| direction_key(*d) == name.trim()) {
+                Some(i) => i,
+                None => continue,
+            };
+            let mut keys = value.split(',').map(|k| parse_key(k.trim()));
+            if let Some(Some(k)) = keys.next() {
+                bindings.primary[i] = k;
+            }
+            if let Some(Some(k)) = keys.next() {
+                bindings.secondary[i] = k;
+            }
+        }
+        bindings
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for (i, d) in DIRECTIONS.iter().enumerate() {
+            out.push_str(&format!("{}={},{}\n", direction_key(*d), key_name(self.primary[i]), key_name(self.secondary[i])));
+        }
+        fs::write(path, out)
+    }
+
+    pub fn direction_for(&self, key: Key) -> Option<Direction> {
+        (0..DIRECTIONS.len())
+            .find(|&i| self.primary[i] == key || self.secondary[i] == key)
+            .map(|i| DIRECTIONS[i])
+    }
+
+    // 修改某个方向的主按键；若该键已绑定到其他方向，则与其交换，避免一键多用
+    pub fn rebind(&mut self, index: usize, key: Key) {
+        for i in 0..DIRECTIONS.len() {
+            if i == index {
+                continue;
+            }
+            if self.primary[i] == key {
+                self.primary[i] = self.primary[index];
+            }
+            if self.secondary[i] == key {
+                self.secondary[i] = self.primary[index];
+            }
+        }
+        self.primary[index] = key;
+    }
+}
+
+pub fn direction_key(dir: Direction) -> &'static str {
+    match dir {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+pub fn parse_direction(name: &str) -> Option<Direction> {
+    DIRECTIONS.iter().copied().find(|d| direction_key(*d) == name)
+}
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+// 按名字查找按键（与 key_name 对应），只查找普通按键与方向键等常用范围
+pub fn parse_key(name: &str) -> Option<Key> {
+    (0u32..0x80)
+        .chain(0x4000_0039..0x4000_0060)
+        .map(Key::from)
+        .find(|k| *k != Key::Unknown && key_name(*k) == name)
+}
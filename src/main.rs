@@ -1,38 +1,236 @@
 extern crate piston_window;
 extern crate rand;
+mod achievements;
+mod ai;
+mod animation;
+mod audio;
+mod bench;
+mod bot;
+mod cli;
 mod draw;
+mod effects;
+mod env;
+mod events;
 mod game;
+mod hud;
+mod keybindings;
 mod snake;
+mod snake_render;
+mod synth;
 mod menu;
-use game::Game;
+mod profile;
+mod render;
+mod replay;
+mod rng;
+mod save;
+mod scene;
+mod settings;
+mod sim;
+mod stats;
+mod theme;
+mod tui;
+use animation::{GifOptions, Highlights};
+use audio::{Audio, MusicTrack, Sound};
+use cli::{Command, PlayOptions};
+use effects::Effects;
+use events::EventListener;
+use game::{Game, Level};
+use hud::{Hud, NotificationCategory};
 use menu::{Menu, MenuState};
-use piston_window::types::Color;
 use piston_window::*;
 use piston_window::{Glyphs, TextureSettings};
-const BACK_COLOR: Color = [0.5, 0.5, 0.5, 1.0];
-const GAME_WIDTH: i32 = 30;
-const GAME_HEIGHT: i32 = 30;
+use profile::{Profiles, PROFILES_DIR};
+use render::PistonRenderer;
+use replay::{Replay, ReplayPlayer, ReplayStep};
+use rng::GameRng;
+use stats::RunStats;
+use theme::Theme;
+const MAX_FRAME_TIME: f64 = 0.05; // 卡顿后单帧最多推进的时间，避免计时一次跳过太多
+
+// 执行一步游戏操作；正在录制回放时同时记下，并放进最近几秒的滚动缓冲
+fn play_step(game: &mut Game, recording: &mut Option<Replay>, highlights: &mut Highlights, step: ReplayStep) {
+    step.apply(game);
+    if let Some(ref mut replay) = recording {
+        replay.steps.push(step);
+    }
+    highlights.push(step);
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match cli::parse(&args) {
+        Ok(Command::Play(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", cli::HELP);
+            return;
+        }
+        // snake --export-sounds <目录>：把合成的音效导出为 WAV 后退出
+        Ok(Command::ExportSounds(dir)) => {
+            match synth::export_sound_effects(&dir) {
+                Ok(()) => println!("音效已导出到 {}", dir),
+                Err(e) => eprintln!("导出音效失败: {}", e),
+            }
+            return;
+        }
+        Ok(Command::Bench { frames, options }) => {
+            bench::run(frames, &options).print();
+            return;
+        }
+        Ok(Command::Simulate(options)) => {
+            print!("{}", sim::run(&options).render(options.format));
+            return;
+        }
+        Ok(Command::Tournament(options)) => {
+            bot::print_standings(&bot::run_tournament(&options));
+            return;
+        }
+        Ok(Command::Gif(export)) => {
+            let replay = match Replay::load(&export.replay) {
+                Ok(replay) => replay,
+                Err(e) => {
+                    eprintln!("无法读取回放 {}: {}", export.replay.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            match animation::Clip::from_replay(&replay, export.window_size).export_gif(&export.output, &export.options) {
+                Ok(frames) => println!("GIF 已保存到 {}（{} 帧）", export.output.display(), frames),
+                Err(e) => {
+                    eprintln!("无法导出 GIF: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Ok(Command::Render(options)) => {
+            match scene::run(&options) {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("渲染失败: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Ok(Command::Env(options)) => {
+            if options.serve {
+                if let Err(e) = env::serve(&options) {
+                    eprintln!("环境服务出错: {}", e);
+                }
+            } else {
+                env::run_random(&options).print();
+            }
+            return;
+        }
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            eprintln!("使用 --help 查看用法");
+            std::process::exit(2);
+        }
+    };
+    if options.tui {
+        match tui::run(&options) {
+            Ok(runs) => {
+                for (i, run) in runs.iter().enumerate() {
+                    let cause = run.death.map_or("", |c| c.label());
+                    println!("第 {} 局: 得分 {}  长度 {}  用时 {}  {}", i + 1, run.score, run.max_length, stats::format_duration(run.duration), cause);
+                }
+            }
+            Err(e) => {
+                eprintln!("无法在终端中运行: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    run_window(options);
+}
+
+fn run_window(options: PlayOptions) {
+    // 回放文件在打开窗口前读取，出错时直接退出
+    let mut player = match options.replay {
+        Some(ref path) => match Replay::load(path) {
+            Ok(replay) => Some(ReplayPlayer::new(replay)),
+            Err(e) => {
+                eprintln!("无法读取回放 {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    // --bot：打开窗口前等待机器人连接
+    let mut bot = options.bot.as_ref().map(|address| {
+        let connection = bot::Listener::bind(address).and_then(|listener| {
+            println!("等待机器人连接 {} ...", listener.address());
+            listener.accept(options.bot_deadline, None)
+        });
+        match connection {
+            Ok(connection) => {
+                println!("机器人已连接");
+                connection
+            }
+            Err(e) => {
+                eprintln!("无法连接机器人 {}: {}", address, e);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    let (window_w, window_h) = options.window_size;
     //https://magiclen.org/rust-compile-optimize/
     let mut window: PistonWindow =
-        WindowSettings::new("贪吃蛇游戏", [800, 600])
-            .exit_on_esc(true)
+        WindowSettings::new("贪吃蛇游戏", [window_w, window_h])
             .resizable(true)
+            .fullscreen(options.fullscreen)
             .build()
             .unwrap();
     // 注意：当前依赖版本不支持直接关闭 IME；如需避免编辑器“打字”，
     // 请确保焦点在游戏窗口（单击游戏窗口或 Alt+Tab 切过去）。
     
-    let mut menu = Menu::new(800.0, 600.0);
+    let mut menu = Menu::new(window_w as f64, window_h as f64);
+    menu.profiles = Profiles::load(PROFILES_DIR);
+    if let Some(ref name) = options.profile {
+        if menu.profiles.select(name).is_err() {
+            eprintln!("玩家 {} 不存在，现有玩家: {}", name, menu.profiles.names().join(", "));
+            std::process::exit(2);
+        }
+    }
+    menu.load_profile();
+    if let Some(mode) = options.mode {
+        menu.selected_mode = mode;
+    }
+    if let Some(speed) = options.speed {
+        menu.selected_speed = speed;
+    }
+    if options.start_directly {
+        menu.state = MenuState::Playing;
+    }
+    let level = options.level.unwrap_or(Level::Normal);
+    let (board_w, board_h) = level.board_size();
     // 加载字体 
-    let font_bytes: &'static [u8] = include_bytes!("../assets/FiraSans-Regular.ttf");
     let mut glyphs = Glyphs::from_bytes(
-        font_bytes,
+        render::FONT_BYTES,
         window.create_texture_context(),
         TextureSettings::new(),
     ).unwrap();
     let mut game: Option<Game> = None;
+    let mut run_stats: Option<RunStats> = None;
+    let mut recording: Option<Replay> = None; // --record 时记录当前这一局
+    let mut highlights = Highlights::new(animation::HIGHLIGHT_SECONDS); // F9 导出 GIF 用
+    let mut effects = Effects::new();
+    let mut hud = Hud::new(window_w as f64, window_h as f64);
+    if let Some(ref player) = player {
+        let replay = player.replay();
+        let mut replay_game = replay.start();
+        replay_game.update_window_size(window_w as f64, window_h as f64);
+        run_stats = Some(RunStats::new(replay.mode, replay_game.speed(), replay_game.snake_length(), replay_game.board_cells()));
+        menu.selected_mode = replay.mode;
+        menu.selected_speed = replay.speed;
+        menu.state = MenuState::Playing;
+        game = Some(replay_game);
+    }
+    let mut audio = Audio::new();
+    println!("音频输出: {}", audio.backend_name());
     let mut cursor_pos = [0.0, 0.0];
     
     // 尝试加载字体
@@ -41,6 +239,9 @@ fn main() {
     }
     
     while let Some(event) = window.next() {
+        // 回放或机器人操控的这一局不是玩家自己的，不能存档
+        menu.can_save = player.is_none() && bot.is_none();
+
         // 更新光标位置
         if let Some(pos) = event.mouse_cursor_args() {
             cursor_pos = pos;
@@ -48,27 +249,157 @@ fn main() {
         
         // 处理鼠标点击事件
         if let Some(Button::Mouse(MouseButton::Left)) = event.press_args() {
-            println!("Mouse button pressed!");
-            println!("Mouse click detected at: ({}, {})", cursor_pos[0], cursor_pos[1]);
+            let before = (menu.state, menu.settings.clone());
             menu.handle_click(cursor_pos[0], cursor_pos[1]);
+            // 点中菜单按钮（界面或设置发生变化）时播放点击音效
+            if before != (menu.state, menu.settings.clone()) {
+                audio.play(Sound::MenuClick);
+            }
         }
         
-        // 检查是否需要创建游戏实例
-        if menu.state == MenuState::Playing && game.is_none() {
-            game = Some(Game::new_with_mode(GAME_WIDTH, GAME_HEIGHT, menu.selected_mode, menu.selected_speed));
+        // 每个界面循环播放对应的背景音乐
+        audio.apply_settings(&menu.settings);
+        hud.apply_settings(&menu.settings);
+        audio.set_music(Some(match menu.state {
+            MenuState::Playing | MenuState::GameMenu => MusicTrack::Game,
+            MenuState::GameOver => MusicTrack::GameOver,
+            _ => MusicTrack::Menu,
+        }));
+
+        // 保存并退出：写入当前玩家的存档后回到主菜单
+        if menu.save_requested {
+            menu.save_requested = false;
+            if let (Some(ref g), Some(ref run)) = (&game, &run_stats) {
+                if g.is_game_over() {
+                    menu.state = MenuState::Playing;
+                } else {
+                    match save::save_session(menu.profiles.path(save::SAVE_FILE), g, run, &hud) {
+                        Ok(()) => {
+                            menu.has_save = true;
+                            menu.status = None;
+                            menu.is_paused = false;
+                            menu.state = MenuState::Main;
+                        }
+                        Err(e) => {
+                            eprintln!("无法保存游戏: {}", e);
+                            menu.status = Some("CANNOT WRITE SAVE FILE".to_string());
+                            menu.state = MenuState::Main;
+                        }
+                    }
+                }
+            }
         }
-        
-        // 检查是否需要重新开始游戏
-        if menu.should_restart {
-            game = Some(Game::new_with_mode(GAME_WIDTH, GAME_HEIGHT, menu.selected_mode, menu.selected_speed));
+
+        // 回到主菜单即放弃当前这一局，之后开始的总是新游戏
+        if menu.state == MenuState::Main && game.is_some() {
+            game = None;
+            run_stats = None;
+            recording = None;
+            highlights.clear();
+            player = None;
+            menu.countdown_request = None;
+        }
+
+        // 继续存档：读取成功后删除存档，失败则把文件改名保留并提示
+        if menu.continue_requested {
+            menu.continue_requested = false;
+            let path = menu.profiles.path(save::SAVE_FILE);
+            match save::load_session(&path, &mut hud) {
+                Ok((mut loaded, run)) => {
+                    loaded.update_window_size(menu.window_width, menu.window_height);
+                    loaded.start_countdown(menu.settings.countdown, true);
+                    if let Some(ref mut bot) = bot {
+                        bot.start(&loaded);
+                    }
+                    menu.selected_mode = loaded.get_game_mode();
+                    menu.selected_speed = loaded.speed();
+                    game = Some(loaded);
+                    run_stats = Some(run);
+                    // 从存档继续的一局无法从头重放，不录制
+                    recording = None;
+                    highlights.clear();
+                    player = None;
+                    effects.clear();
+                    menu.is_paused = false;
+                    menu.status = None;
+                    menu.state = MenuState::Playing;
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        eprintln!("无法删除存档: {}", e);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("无法读取存档: {}", e);
+                    let _ = std::fs::rename(&path, path.with_extension("cfg.bad"));
+                    menu.status = Some(e.label());
+                }
+            }
+            menu.has_save = path.exists();
+        }
+
+        // 检查是否需要创建游戏实例或重新开始游戏
+        if (menu.state == MenuState::Playing && game.is_none()) || menu.should_restart {
+            // 每局都有确定的种子，录制的回放才能重现食物位置
+            let seed = options.seed.unwrap_or_else(|| GameRng::from_entropy().next_u64());
+            let mut new_game = Game::new_seeded(board_w, board_h, menu.selected_mode, menu.selected_speed, seed);
+            new_game.update_window_size(menu.window_width, menu.window_height);
+            recording = options.record.as_ref().map(|_| Replay::new(menu.selected_mode, menu.selected_speed, level, seed));
+            player = None;
+            highlights.clear();
+            play_step(&mut new_game, &mut recording, &mut highlights, ReplayStep::Countdown(menu.settings.countdown, true));
+            if let Some(ref mut bot) = bot {
+                bot.start(&new_game);
+            }
+            run_stats = Some(RunStats::new(menu.selected_mode, new_game.speed(), new_game.snake_length(), new_game.board_cells()));
+            game = Some(new_game);
+            effects.clear();
+            hud.clear();
             menu.should_restart = false;
         }
         
+        // 切到其他窗口或最小化时自动暂停，切回来后倒数继续
+        if let Some(focused) = event.focus_args() {
+            let running = game.as_ref().is_some_and(|g| !g.is_game_over());
+            if !focused && running {
+                menu.pause_for_focus_loss();
+            } else if focused {
+                menu.resume_after_focus();
+            }
+        }
+
+        // 取消暂停后先倒数再继续
+        if let Some(seconds) = menu.countdown_request.take() {
+            if let (Some(ref mut game), None) = (&mut game, &player) {
+                play_step(game, &mut recording, &mut highlights, ReplayStep::Countdown(seconds, false));
+            }
+        }
+
         // 处理键盘事件
         if let Some(Button::Keyboard(key)) = event.press_args() {
-            if menu.state == MenuState::Playing {
-                if let Some(ref mut game) = game {
-                    game.key_pressed(key);
+            // F12 截图，任何界面都可以
+            if key == Key::F12 {
+                match scene::save_screenshot(&menu, game.as_ref(), &effects, &hud) {
+                    Ok(path) => println!("截图已保存到 {}", path.display()),
+                    Err(e) => eprintln!("无法保存截图: {}", e),
+                }
+            } else if key == Key::F9 {
+                // F9 把这一局最近几秒导出为 GIF；离屏绘制与编码较慢，放到后台线程
+                let clip = highlights.clip(&menu.settings, (menu.window_width, menu.window_height));
+                match (clip, scene::capture_path("gif")) {
+                    (Some(clip), Ok(path)) => {
+                        println!("正在导出最近 {} 秒的 GIF ...", highlights.seconds());
+                        let options = GifOptions { last: Some(highlights.seconds()), ..GifOptions::default() };
+                        std::thread::spawn(move || match clip.export_gif(&path, &options) {
+                            Ok(frames) => println!("GIF 已保存到 {}（{} 帧）", path.display(), frames),
+                            Err(e) => eprintln!("无法导出 GIF: {}", e),
+                        });
+                    }
+                    (None, _) => println!("还没有可导出的画面"),
+                    (_, Err(e)) => eprintln!("无法导出 GIF: {}", e),
+                }
+            } else if menu.state == MenuState::Playing && key != Key::Escape {
+                // 播放回放或由机器人操控时不接受方向键
+                if let (Some(ref mut game), Some(d), None, None) = (&mut game, menu.bindings.direction_for(key), &player, &bot) {
+                    play_step(game, &mut recording, &mut highlights, ReplayStep::Turn(d));
                 }
             } else {
                 menu.handle_key(key);
@@ -78,17 +409,20 @@ fn main() {
         // 兼容某些平台/输入法将字母键作为文本事件而非键盘事件投递的情况
         if let Some(text) = event.text_args() {
             if menu.state == MenuState::Playing {
-                if let Some(ref mut game) = game {
+                if let (Some(ref mut game), None, None) = (&mut game, &player, &bot) {
                     for ch in text.chars() {
-                        match ch {
-                            'w' | 'W' => game.key_pressed(Key::W),
-                            'a' | 'A' => game.key_pressed(Key::A),
-                            's' | 'S' => game.key_pressed(Key::S),
-                            'd' | 'D' => game.key_pressed(Key::D),
-                            _ => {}
+                        // 字母键的按键码即其小写 ASCII 码
+                        if !ch.is_ascii_alphanumeric() {
+                            continue;
+                        }
+                        if let Some(d) = menu.bindings.direction_for(Key::from(ch.to_ascii_lowercase() as u32)) {
+                            play_step(game, &mut recording, &mut highlights, ReplayStep::Turn(d));
                         }
                     }
                 }
+            } else {
+                // 菜单中的文字输入框（玩家名等）
+                menu.handle_text(&text);
             }
         }
         
@@ -96,61 +430,86 @@ fn main() {
         if let Some(args) = event.resize_args() {
             let [width, height] = args.window_size;
             menu.update_window_size(width, height);
+            hud.update_window_size(width, height);
             if let Some(ref mut game) = game {
                 game.update_window_size(width, height);
             }
         }
         
         window.draw_2d(&event, |c, g, device| {
-            clear(BACK_COLOR, g);
-            
-            match menu.state {
-                MenuState::Main | MenuState::ModeSelection | MenuState::SpeedSelection | MenuState::ConfirmStart => {
-                    menu.draw(&c, g, &mut glyphs);
-                }
-                MenuState::Playing => {
-                    if let Some(ref game) = game {
-                        game.draw(&c, g);
-                        // 绘制分数
-                        menu.draw_score(game.get_score(), &c, g, &mut glyphs);
-                        // 限时模式：绘制倒计时
-                        if let Some(rt) = game.get_remaining_time() {
-                            let min = (rt as i32) / 60;
-                            let s = (rt as i32) % 60;
-                            let time_text = format!("TIME {:02}:{:02}", min, s);
-                            menu.draw_text_top_right(&time_text, 24.0, [1.0, 1.0, 0.0, 1.0], &c, g, &mut glyphs);
-                        }
-                        // 绘制暂停指示器
-                        menu.draw_pause_indicator(&c, g, &mut glyphs);
-                        // 绘制游戏消息
-                        game.draw_messages(&c, g, &mut glyphs);
-                    }
-                    menu.draw(&c, g, &mut glyphs); // 绘制游戏内菜单按钮
-                }
-                MenuState::GameMenu => {
-                    if let Some(ref game) = game {
-                        game.draw(&c, g);
-                    }
-                    menu.draw(&c, g, &mut glyphs); // 绘制游戏内菜单
-                }
-                MenuState::GameOver => {
-                    if let Some(ref game) = game {
-                        game.draw(&c, g);
-                    }
-                    menu.draw(&c, g, &mut glyphs); // 绘制GameOver菜单
-                }
-            }
+            scene::draw_frame(&mut PistonRenderer::new(c, g, &mut glyphs), &menu, game.as_ref(), &effects, &hud);
             // 刷新字体缓冲，避免只绘制首字符的问题
             glyphs.factory.encoder.flush(device);
         });
         
         event.update(|arg| {
+            let dt = arg.dt.min(MAX_FRAME_TIME);
             if menu.state == MenuState::Playing && !menu.is_paused {
-                if let Some(ref mut game) = game {
-                    game.update(arg.dt);
-                    // 检查游戏是否结束
-                    if game.is_game_over() {
-                        menu.set_final_score(game.get_score());
+                if let Some(ref game) = game {
+                    if !game.is_game_over() {
+                        highlights.checkpoint(game, &effects, &hud);
+                    }
+                }
+                effects.update(dt);
+                hud.update(dt);
+                if let (Some(ref mut game), Some(ref mut run_stats)) = (&mut game, &mut run_stats) {
+                    if !game.is_game_over() {
+                        // 倒数期间不计入本局用时
+                        let counting = game.is_counting_down();
+                        match player {
+                            // 回放按录制时的 dt 逐帧推进，放完仍未结束则回到主菜单
+                            Some(ref mut p) => match p.next_frame() {
+                                Some(steps) => {
+                                    for &step in steps {
+                                        play_step(game, &mut recording, &mut highlights, step);
+                                    }
+                                }
+                                None => {
+                                    menu.status = Some("REPLAY FINISHED".to_string());
+                                    menu.state = MenuState::Main;
+                                    return;
+                                }
+                            },
+                            None => {
+                                // 机器人的转向和玩家按键一样录入回放
+                                if let Some(d) = bot.as_mut().and_then(|b| b.poll(game)) {
+                                    play_step(game, &mut recording, &mut highlights, ReplayStep::Turn(d));
+                                }
+                                play_step(game, &mut recording, &mut highlights, ReplayStep::Update(dt));
+                            }
+                        }
+                        if !counting {
+                            run_stats.update(dt);
+                        }
+                    }
+                    // 把本帧的游戏事件分发给各个订阅者
+                    effects.set_theme(Theme::from_palette(menu.settings.palette));
+                    let listeners: &mut [&mut dyn EventListener] = &mut [&mut effects, &mut hud, &mut audio, run_stats];
+                    events::dispatch(&game.drain_events(), listeners);
+                    // 游戏中达成成就时立即提示并保存（回放与机器人不计）
+                    let unlocked = if player.is_none() && bot.is_none() { menu.achievements.evaluate(run_stats) } else { Vec::new() };
+                    if !unlocked.is_empty() {
+                        for a in unlocked {
+                            hud.notify(NotificationCategory::Achievement, format!("Unlocked: {}", a.name));
+                        }
+                        menu.save_achievements();
+                    }
+                    // 检查游戏是否结束（等死亡动画播放完再显示菜单）
+                    if game.is_game_over() && !effects.is_dying() {
+                        if let Some(ref mut bot) = bot {
+                            bot.finish(run_stats);
+                        }
+                        if player.is_some() || bot.is_some() {
+                            menu.show_replay_result(run_stats.clone());
+                        } else {
+                            menu.finish_run(run_stats.clone());
+                        }
+                        if let (Some(ref path), Some(ref replay)) = (&options.record, &recording) {
+                            match replay.save(path) {
+                                Ok(()) => println!("回放已保存到 {}", path.display()),
+                                Err(e) => eprintln!("无法保存回放: {}", e),
+                            }
+                        }
                         menu.state = MenuState::GameOver;
                     }
                 }
@@ -1,6 +1,18 @@
 use piston_window::*;
 use std::path::Path;
 
+use crate::achievements::{Achievements, ACHIEVEMENTS, ACHIEVEMENTS_FILE};
+use crate::keybindings::{direction_key, key_name, KeyBindings, DIRECTIONS, KEYS_FILE};
+use crate::profile::{Profiles, TextInput, MAX_NAME_LEN, MAX_PROFILES};
+use crate::save::SAVE_FILE;
+use crate::stats::{
+    format_duration, BestRecord, HighScores, LifetimeStats, Records, RunStats, HIGH_SCORES_FILE, LIFETIME_FILE,
+    RECORDS_FILE,
+};
+use crate::game::CountdownPhase;
+use crate::render::Renderer;
+use crate::settings::{next_countdown, next_notification_duration, next_volume, Settings, SETTINGS_FILE};
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameSpeed {
     Slow,
@@ -8,6 +20,23 @@ pub enum GameSpeed {
     Fast,
 }
 
+impl GameSpeed {
+    pub const ALL: [GameSpeed; 3] = [GameSpeed::Slow, GameSpeed::Medium, GameSpeed::Fast];
+
+    // 写入存档时使用的名字
+    pub fn key(&self) -> &'static str {
+        match *self {
+            GameSpeed::Slow => "slow",
+            GameSpeed::Medium => "medium",
+            GameSpeed::Fast => "fast",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<GameSpeed> {
+        GameSpeed::ALL.iter().copied().find(|s| s.key() == key)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameMode {
     Classic,
@@ -15,6 +44,35 @@ pub enum GameMode {
     Survival,
 }
 
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::Speed, GameMode::Survival];
+
+    pub fn label(&self) -> &'static str {
+        match *self {
+            GameMode::Classic => "CLASSIC",
+            GameMode::Speed => "SPEED",
+            GameMode::Survival => "SURVIVAL",
+        }
+    }
+
+    // 保存记录时使用的名字
+    pub fn key(&self) -> &'static str {
+        match *self {
+            GameMode::Classic => "classic",
+            GameMode::Speed => "speed",
+            GameMode::Survival => "survival",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<GameMode> {
+        GameMode::ALL.iter().copied().find(|m| m.key() == key)
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MenuState {
     Main,
@@ -24,6 +82,19 @@ pub enum MenuState {
     Playing,
     GameMenu,
     GameOver,
+    Settings,
+    Profile,
+    Achievements,
+    Players,  // 玩家选择与管理
+    Controls, // 按键设置
+}
+
+// 当前文字输入框的用途
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEntryPurpose {
+    NewPlayer,
+    RenamePlayer,
+    HighScoreName,
 }
 
 pub struct Menu {
@@ -35,7 +106,25 @@ pub struct Menu {
     pub window_height: f64,
     pub is_paused: bool,
     pub should_restart: bool,
-    pub final_score: i32,
+    pub has_save: bool,           // 当前玩家有未完成的存档，主菜单显示 CONTINUE
+    pub save_requested: bool,     // 游戏内菜单点了 SAVE & QUIT，由主循环写入存档
+    pub can_save: bool,           // 回放或机器人操控时不能存档，SAVE & QUIT 变灰
+    pub continue_requested: bool, // 主菜单点了 CONTINUE，由主循环读取存档
+    pub auto_paused: bool,        // 因窗口失去焦点而自动暂停
+    pub countdown_request: Option<u32>, // 取消暂停后由主循环让游戏倒数的秒数
+    pub last_run: Option<RunStats>, // 刚结束的一局，结算界面显示
+    pub previous_best: BestRecord,  // 这一局之前的最好成绩，用于对比
+    pub records: Records,
+    pub lifetime: LifetimeStats,
+    pub achievements: Achievements,
+    pub settings: Settings,
+    pub bindings: KeyBindings,
+    pub profiles: Profiles,
+    pub high_scores: HighScores,
+    pub text_entry: Option<(TextEntryPurpose, TextInput)>,
+    pub pending_high_score: Option<(GameMode, i32)>, // 等待输入名字后记入排行榜的成绩
+    pub awaiting_key: Option<usize>, // 按键设置页中等待按下新按键的方向
+    pub status: Option<String>,      // 玩家页、主菜单的错误提示
 }
 
 impl Menu {
@@ -49,14 +138,42 @@ impl Menu {
             window_height,
             is_paused: false,
             should_restart: false,
-            final_score: 0,
+            has_save: false,
+            save_requested: false,
+            can_save: true,
+            continue_requested: false,
+            auto_paused: false,
+            countdown_request: None,
+            last_run: None,
+            previous_best: BestRecord::default(),
+            records: Records::default(),
+            lifetime: LifetimeStats::default(),
+            achievements: Achievements::default(),
+            settings: Settings::default(),
+            bindings: KeyBindings::default(),
+            profiles: Profiles::default(),
+            high_scores: HighScores::default(),
+            text_entry: None,
+            pending_high_score: None,
+            awaiting_key: None,
+            status: None,
         }
     }
 
+    // 读取当前玩家的设置、按键、成绩、排行榜、统计与成就
+    pub fn load_profile(&mut self) {
+        self.settings = Settings::load(self.profiles.path(SETTINGS_FILE));
+        self.bindings = KeyBindings::load(self.profiles.path(KEYS_FILE));
+        self.records = Records::load(self.profiles.path(RECORDS_FILE));
+        self.lifetime = LifetimeStats::load(self.profiles.path(LIFETIME_FILE));
+        self.achievements = Achievements::load(self.profiles.path(ACHIEVEMENTS_FILE));
+        self.high_scores = HighScores::load(self.profiles.path(HIGH_SCORES_FILE));
+        self.has_save = self.profiles.path(SAVE_FILE).exists();
+    }
+
     pub fn load_font(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // 使用编译期内嵌字体，避免发布版找不到路径
-        let font_bytes: &'static [u8] = include_bytes!("../assets/FiraSans-Regular.ttf");
-        if let Some(font) = rusttype::Font::try_from_vec(font_bytes.to_vec()) {
+        if let Some(font) = rusttype::Font::try_from_vec(crate::render::FONT_BYTES.to_vec()) {
             self.font = Some(font);
             return Ok(());
         }
@@ -68,35 +185,185 @@ impl Menu {
         self.window_height = new_height;
     }
     
-    pub fn set_final_score(&mut self, score: i32) {
-        self.final_score = score;
+    // 离开游戏内菜单回到游戏。菜单打开时游戏是冻结的，所以没有暂停就先倒数再继续
+    fn close_game_menu(&mut self) {
+        if !self.is_paused {
+            self.countdown_request = Some(self.settings.countdown);
+        }
+        self.state = MenuState::Playing;
+    }
+
+    // 窗口失去焦点（切到其他程序或最小化）时打开暂停菜单
+    pub fn pause_for_focus_loss(&mut self) {
+        if self.state == MenuState::Playing && !self.is_paused {
+            self.is_paused = true;
+            self.auto_paused = true;
+            self.state = MenuState::GameMenu;
+        }
+    }
+
+    // 重新获得焦点：若暂停是自动打开的，关闭菜单并按设置的秒数倒数后继续
+    pub fn resume_after_focus(&mut self) {
+        if !self.auto_paused {
+            return;
+        }
+        self.auto_paused = false;
+        if self.state == MenuState::GameMenu && self.is_paused {
+            self.is_paused = false;
+            self.state = MenuState::Playing;
+            self.countdown_request = Some(self.settings.countdown);
+        }
+    }
+
+    // 一局结束：记入最好成绩与累计统计并保存，结算界面显示这一局
+    pub fn finish_run(&mut self, run: RunStats) {
+        self.previous_best = self.records.submit(&run);
+        if let Err(e) = self.records.save(self.profiles.path(RECORDS_FILE)) {
+            eprintln!("无法保存记录: {}", e);
+        }
+        self.lifetime.record(&run);
+        if let Err(e) = self.lifetime.save(self.profiles.path(LIFETIME_FILE)) {
+            eprintln!("无法保存统计: {}", e);
+        }
+        self.achievements.evaluate(&run);
+        self.save_achievements();
+        // 进入排行榜时先让玩家确认名字，默认为当前玩家名
+        if self.high_scores.qualifies(run.mode, run.score) {
+            self.pending_high_score = Some((run.mode, run.score));
+            self.text_entry = Some((TextEntryPurpose::HighScoreName, TextInput::new(self.profiles.active(), MAX_NAME_LEN)));
+        }
+        self.last_run = Some(run);
+    }
+
+    // 回放或机器人的一局结束：只显示结算，不计入记录、统计与成就
+    pub fn show_replay_result(&mut self, run: RunStats) {
+        self.previous_best = self.records.best(run.mode);
+        self.last_run = Some(run);
+    }
+
+    pub fn save_achievements(&self) {
+        if let Err(e) = self.achievements.save(self.profiles.path(ACHIEVEMENTS_FILE)) {
+            eprintln!("无法保存成就: {}", e);
+        }
+    }
+
+    // 把待记入的成绩写进排行榜；名字为空时使用当前玩家名
+    fn commit_high_score(&mut self) {
+        let (mode, score) = match self.pending_high_score.take() {
+            Some(p) => p,
+            None => return,
+        };
+        let name = match self.text_entry.take() {
+            Some((TextEntryPurpose::HighScoreName, input)) if !input.text.is_empty() => input.text,
+            _ => self.profiles.active().to_string(),
+        };
+        self.high_scores.insert(mode, &name, score);
+        if let Err(e) = self.high_scores.save(self.profiles.path(HIGH_SCORES_FILE)) {
+            eprintln!("无法保存排行榜: {}", e);
+        }
+    }
+
+    // 切换玩家后重新读取该玩家的数据
+    fn switch_player(&mut self, name: &str) {
+        match self.profiles.select(name) {
+            Ok(()) => {
+                self.load_profile();
+                self.status = None;
+            }
+            Err(e) => self.status = Some(e.label()),
+        }
+    }
+
+    // 确认玩家页的名字输入
+    fn confirm_player_name(&mut self, purpose: TextEntryPurpose, name: &str) {
+        let result = match purpose {
+            TextEntryPurpose::NewPlayer => self.profiles.create(name),
+            TextEntryPurpose::RenamePlayer => {
+                let old = self.profiles.active().to_string();
+                self.profiles.rename(&old, name)
+            }
+            TextEntryPurpose::HighScoreName => Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                self.load_profile();
+                self.status = None;
+            }
+            Err(e) => self.status = Some(e.label()),
+        }
+    }
+
+    // 文字输入事件，只在有输入框时使用
+    pub fn handle_text(&mut self, text: &str) {
+        if let Some((_, ref mut input)) = self.text_entry {
+            input.push_str(text);
+        }
+    }
+
+    // 输入框打开时处理退格、回车与 ESC；返回 true 表示按键已被输入框处理
+    fn handle_text_entry_key(&mut self, key: Key) -> bool {
+        let purpose = match self.text_entry {
+            Some((purpose, _)) => purpose,
+            None => return false,
+        };
+        match key {
+            Key::Backspace => {
+                if let Some((_, ref mut input)) = self.text_entry {
+                    input.backspace();
+                }
+            }
+            Key::Return | Key::NumPadEnter => {
+                if purpose == TextEntryPurpose::HighScoreName {
+                    self.commit_high_score();
+                } else if let Some((_, input)) = self.text_entry.take() {
+                    self.confirm_player_name(purpose, &input.text);
+                }
+            }
+            Key::Escape => {
+                // 排行榜名字取消时仍以当前玩家名记入
+                if purpose == TextEntryPurpose::HighScoreName {
+                    self.text_entry = None;
+                    self.commit_high_score();
+                } else {
+                    self.text_entry = None;
+                }
+            }
+            _ => {}
+        }
+        true
     }
 
     pub fn handle_click(&mut self, x: f64, y: f64) {
-        println!("Menu handle_click: ({}, {}) in state {:?}", x, y, self.state);
         match self.state {
             MenuState::Main => {
                 // 主页面按钮区域
                 let center_x = self.window_width / 2.0;
                 let center_y = self.window_height / 2.0;
-                
-                println!("Window: {}x{}, center: ({}, {})", self.window_width, self.window_height, center_x, center_y);
-                println!("START button area: x[{}, {}], y[{}, {}]", center_x - 150.0, center_x + 150.0, center_y - 80.0, center_y + 20.0);
-                println!("EXIT button area: x[{}, {}], y[{}, {}]", center_x - 150.0, center_x + 150.0, center_y + 10.0, center_y + 110.0);
-                
-                // 游戏开始按钮 (扩大点击区域进行测试)
-                if x >= center_x - 150.0 && x <= center_x + 150.0 && 
-                   y >= center_y - 80.0 && y <= center_y + 20.0 {
-                    println!("START GAME button clicked!");
+
+                // 继续存档按钮，仅在有存档时显示
+                if self.has_save && in_button(x, y, center_x, center_y - 100.0, 200.0, 50.0) {
+                    self.continue_requested = true;
+                }
+                // 游戏开始按钮
+                else if in_button(x, y, center_x, center_y - 40.0, 200.0, 50.0) {
                     self.state = MenuState::ModeSelection;
                 }
-                // 退出按钮 (扩大点击区域进行测试)
-                else if x >= center_x - 150.0 && x <= center_x + 150.0 && 
-                        y >= center_y + 10.0 && y <= center_y + 110.0 {
-                    println!("EXIT GAME button clicked!");
+                // 设置按钮
+                else if in_button(x, y, center_x, center_y + 20.0, 200.0, 50.0) {
+                    self.state = MenuState::Settings;
+                }
+                // 资料页按钮
+                else if in_button(x, y, center_x, center_y + 80.0, 200.0, 50.0) {
+                    self.state = MenuState::Profile;
+                }
+                // 玩家切换按钮
+                else if in_button(x, y, center_x, center_y + 200.0, 300.0, 40.0) {
+                    self.status = None;
+                    self.state = MenuState::Players;
+                }
+                // 退出按钮
+                else if in_button(x, y, center_x, center_y + 140.0, 200.0, 50.0) {
                     std::process::exit(0);
-                } else {
-                    println!("Click not in any button area");
                 }
             }
             MenuState::ModeSelection => {
@@ -181,53 +448,190 @@ impl Menu {
                 let center_x = self.window_width / 2.0;
                 let center_y = self.window_height / 2.0;
                 
-                // 暂停/继续按钮 (y: center_y - 30 到 center_y + 10)
-                if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                   y >= center_y - 30.0 && y <= center_y + 10.0 {
+                // 暂停/继续按钮
+                if in_button(x, y, center_x, center_y - 50.0, 200.0, 40.0) {
                     self.is_paused = !self.is_paused;
-                    self.state = MenuState::Playing;
+                    self.close_game_menu();
                 }
-                // 重新开始按钮 (y: center_y + 20 到 center_y + 60)
-                else if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                        y >= center_y + 20.0 && y <= center_y + 60.0 {
+                // 重新开始按钮
+                else if in_button(x, y, center_x, center_y, 200.0, 40.0) {
                     // 重新开始游戏
                     self.is_paused = false;
                     self.should_restart = true;
                     self.state = MenuState::Playing;
                 }
-                // 返回主菜单按钮 (y: center_y + 70 到 center_y + 110)
-                else if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                        y >= center_y + 70.0 && y <= center_y + 110.0 {
+                // 保存并退出按钮：主循环写入存档后回到主菜单
+                else if in_button(x, y, center_x, center_y + 50.0, 200.0, 40.0) {
+                    self.save_requested = self.can_save;
+                }
+                // 返回主菜单按钮（放弃当前这一局）
+                else if in_button(x, y, center_x, center_y + 100.0, 200.0, 40.0) {
                     self.state = MenuState::Main;
                 }
-                // 关闭菜单按钮 (y: center_y + 120 到 center_y + 160)
-                else if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                        y >= center_y + 120.0 && y <= center_y + 160.0 {
-                    self.state = MenuState::Playing;
+                // 关闭菜单按钮
+                else if in_button(x, y, center_x, center_y + 150.0, 200.0, 40.0) {
+                    self.close_game_menu();
                 }
             }
             MenuState::GameOver => {
                 let center_x = self.window_width / 2.0;
                 let center_y = self.window_height / 2.0;
                 
-                // 重新开始按钮 (y: center_y - 20 到 center_y + 20)
-                if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                   y >= center_y - 20.0 && y <= center_y + 20.0 {
+                // 重新开始按钮
+                if in_button(x, y, center_x, center_y + 150.0, 200.0, 40.0) {
                     // 重新开始游戏
+                    self.commit_high_score();
                     self.is_paused = false;
                     self.should_restart = true;
                     self.state = MenuState::Playing;
                 }
-                // 返回主菜单按钮 (y: center_y + 40 到 center_y + 80)
-                else if x >= center_x - 100.0 && x <= center_x + 100.0 && 
-                        y >= center_y + 40.0 && y <= center_y + 80.0 {
+                // 返回主菜单按钮
+                else if in_button(x, y, center_x, center_y + 200.0, 200.0, 40.0) {
+                    self.commit_high_score();
+                    self.state = MenuState::Main;
+                }
+            }
+            MenuState::Settings => {
+                let center_x = self.window_width / 2.0;
+                let row_count = self.settings_rows().len();
+                for i in 0..row_count {
+                    if in_button(x, y, center_x, self.settings_row_y(i), 360.0, 34.0) {
+                        self.activate_setting(i);
+                        self.save_settings();
+                        return;
+                    }
+                }
+                // 返回
+                if in_button(x, y, center_x, self.settings_row_y(row_count) + 16.0, 200.0, 40.0) {
+                    self.state = MenuState::Main;
+                }
+            }
+            MenuState::Profile => {
+                let center_x = self.window_width / 2.0;
+                if in_button(x, y, center_x - 110.0, self.window_height - 40.0, 200.0, 40.0) {
+                    self.state = MenuState::Achievements;
+                } else if in_button(x, y, center_x + 110.0, self.window_height - 40.0, 200.0, 40.0) {
+                    self.state = MenuState::Main;
+                }
+            }
+            MenuState::Achievements => {
+                if in_button(x, y, self.window_width / 2.0, self.window_height - 40.0, 200.0, 40.0) {
+                    self.state = MenuState::Profile;
+                }
+            }
+            MenuState::Players => {
+                // 输入名字时忽略点击
+                if self.text_entry.is_some() {
+                    return;
+                }
+                let center_x = self.window_width / 2.0;
+                let names: Vec<String> = self.profiles.names().to_vec();
+                for (i, name) in names.iter().enumerate() {
+                    if in_button(x, y, center_x, player_row_y(i), 360.0, 34.0) {
+                        self.switch_player(name);
+                        return;
+                    }
+                }
+                let buttons_y = player_row_y(MAX_PROFILES) + 10.0;
+                if in_button(x, y, center_x - 160.0, buttons_y, 150.0, 36.0) {
+                    self.status = None;
+                    self.text_entry = Some((TextEntryPurpose::NewPlayer, TextInput::new("", MAX_NAME_LEN)));
+                } else if in_button(x, y, center_x, buttons_y, 150.0, 36.0) {
+                    self.status = None;
+                    self.text_entry = Some((TextEntryPurpose::RenamePlayer, TextInput::new(self.profiles.active(), MAX_NAME_LEN)));
+                } else if in_button(x, y, center_x + 160.0, buttons_y, 150.0, 36.0) {
+                    let active = self.profiles.active().to_string();
+                    match self.profiles.delete(&active) {
+                        Ok(()) => {
+                            self.load_profile();
+                            self.status = None;
+                        }
+                        Err(e) => self.status = Some(e.label()),
+                    }
+                } else if in_button(x, y, center_x, self.window_height - 40.0, 200.0, 40.0) {
                     self.state = MenuState::Main;
                 }
             }
+            MenuState::Controls => {
+                let center_x = self.window_width / 2.0;
+                for i in 0..DIRECTIONS.len() {
+                    if in_button(x, y, center_x, controls_row_y(i), 360.0, 40.0) {
+                        self.awaiting_key = Some(i);
+                        return;
+                    }
+                }
+                if in_button(x, y, center_x, self.window_height - 40.0, 200.0, 40.0) {
+                    self.awaiting_key = None;
+                    self.state = MenuState::Settings;
+                }
+            }
+        }
+    }
+
+    // 设置页每一行显示的文字，顺序与 activate_setting 对应
+    fn settings_rows(&self) -> Vec<String> {
+        let on_off = |b: bool| if b { "ON" } else { "OFF" };
+        vec![
+            format!("PALETTE: {}", self.settings.palette.label()),
+            format!("SHAPE CUES: {}", on_off(self.settings.shape_cues)),
+            format!("SNAKE: {}", self.settings.snake_style.label()),
+            format!("MOVEMENT: {}", if self.settings.smooth_movement { "SMOOTH" } else { "CLASSIC" }),
+            format!("MASTER VOLUME: {}%", self.settings.master_volume),
+            format!("MUSIC VOLUME: {}%", self.settings.music_volume),
+            format!("SFX VOLUME: {}%", self.settings.sfx_volume),
+            format!("NOTIFICATIONS: {}S", self.settings.notification_duration),
+            match self.settings.countdown {
+                0 => "COUNTDOWN: OFF".to_string(),
+                n => format!("COUNTDOWN: {}S", n),
+            },
+            "CONTROLS...".to_string(),
+        ]
+    }
+
+    fn settings_row_y(&self, index: usize) -> f64 {
+        self.window_height / 2.0 - 150.0 + index as f64 * 40.0
+    }
+
+    // 点击设置页某一行：循环切换该项的值
+    fn activate_setting(&mut self, index: usize) {
+        match index {
+            0 => self.settings.palette = self.settings.palette.next(),
+            1 => self.settings.shape_cues = !self.settings.shape_cues,
+            2 => self.settings.snake_style = self.settings.snake_style.next(),
+            3 => self.settings.smooth_movement = !self.settings.smooth_movement,
+            4 => self.settings.master_volume = next_volume(self.settings.master_volume),
+            5 => self.settings.music_volume = next_volume(self.settings.music_volume),
+            6 => self.settings.sfx_volume = next_volume(self.settings.sfx_volume),
+            7 => self.settings.notification_duration = next_notification_duration(self.settings.notification_duration),
+            8 => self.settings.countdown = next_countdown(self.settings.countdown),
+            9 => {
+                self.awaiting_key = None;
+                self.state = MenuState::Controls;
+            }
+            _ => {}
+        }
+    }
+
+    fn save_settings(&self) {
+        if let Err(e) = self.settings.save(self.profiles.path(SETTINGS_FILE)) {
+            eprintln!("无法保存设置: {}", e);
         }
     }
 
     pub fn handle_key(&mut self, key: Key) {
+        if self.handle_text_entry_key(key) {
+            return;
+        }
+        // 按键设置页：下一个按键作为新的绑定，ESC 取消
+        if let Some(index) = self.awaiting_key.take() {
+            if key != Key::Escape {
+                self.bindings.rebind(index, key);
+                if let Err(e) = self.bindings.save(self.profiles.path(KEYS_FILE)) {
+                    eprintln!("无法保存按键设置: {}", e);
+                }
+            }
+            return;
+        }
         match self.state {
             MenuState::Main => {
                 if key == Key::Escape {
@@ -256,7 +660,7 @@ impl Menu {
             }
             MenuState::GameMenu => {
                 if key == Key::Escape {
-                    self.state = MenuState::Playing;
+                    self.close_game_menu();
                 }
             }
             MenuState::GameOver => {
@@ -264,167 +668,403 @@ impl Menu {
                     self.state = MenuState::Main;
                 }
             }
+            MenuState::Settings | MenuState::Profile => {
+                if key == Key::Escape {
+                    self.state = MenuState::Main;
+                }
+            }
+            MenuState::Achievements => {
+                if key == Key::Escape {
+                    self.state = MenuState::Profile;
+                }
+            }
+            MenuState::Players => {
+                if key == Key::Escape {
+                    self.state = MenuState::Main;
+                }
+            }
+            MenuState::Controls => {
+                if key == Key::Escape {
+                    self.state = MenuState::Settings;
+                }
+            }
         }
     }
 
-    pub fn draw(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    pub fn draw(&self, r: &mut dyn Renderer) {
         match self.state {
-            MenuState::Main => self.draw_main_menu(con, g, glyphs),
-            MenuState::ModeSelection => self.draw_mode_selection(con, g, glyphs),
-            MenuState::SpeedSelection => self.draw_speed_selection(con, g, glyphs),
-            MenuState::ConfirmStart => self.draw_confirm_start(con, g, glyphs),
+            MenuState::Main => self.draw_main_menu(r),
+            MenuState::ModeSelection => self.draw_mode_selection(r),
+            MenuState::SpeedSelection => self.draw_speed_selection(r),
+            MenuState::ConfirmStart => self.draw_confirm_start(r),
             MenuState::Playing => {
                 // 游戏进行中，绘制菜单按钮
-                self.draw_game_menu_button(con, g);
+                self.draw_game_menu_button(r);
             }
             MenuState::GameMenu => {
                 // 绘制游戏内菜单
-                self.draw_game_menu(con, g, glyphs);
+                self.draw_game_menu(r);
             }
             MenuState::GameOver => {
                 // 绘制GameOver菜单
-                self.draw_game_over_menu(con, g, glyphs);
+                self.draw_game_over_menu(r);
             }
+            MenuState::Settings => self.draw_settings(r),
+            MenuState::Profile => self.draw_profile(r),
+            MenuState::Achievements => self.draw_achievements(r),
+            MenuState::Players => self.draw_players(r),
+            MenuState::Controls => self.draw_controls(r),
         }
     }
 
-    fn draw_main_menu(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    fn draw_main_menu(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
 
         // 绘制背景
-        rectangle(
+        r.fill_rect(
             [0.1, 0.1, 0.1, 1.0], // 深色背景
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
         // 绘制标题（使用字体）
-        self.draw_text_glyph("SNAKE GAME", center_x + 2.0, center_y - 118.0, 48, [0.0, 0.0, 0.0, 0.8], con, g, glyphs);
-        self.draw_text_glyph("SNAKE GAME", center_x, center_y - 120.0, 48, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("SNAKE GAME", center_x + 2.0, center_y - 168.0, 48, [0.0, 0.0, 0.0, 0.8], r);
+        self.draw_text_glyph("SNAKE GAME", center_x, center_y - 170.0, 48, [1.0, 1.0, 1.0, 1.0], r);
+
+        // 有存档时绘制继续按钮
+        if self.has_save {
+            self.draw_button_glyph("CONTINUE", center_x, center_y - 100.0, 200.0, 50.0, [0.2, 0.5, 0.5, 1.0], r);
+        }
 
         // 绘制游戏开始按钮
-        self.draw_button_glyph("START GAME", center_x, center_y - 20.0, 200.0, 50.0, [0.2, 0.6, 0.2, 1.0], con, g, glyphs);
+        self.draw_button_glyph("START GAME", center_x, center_y - 40.0, 200.0, 50.0, [0.2, 0.6, 0.2, 1.0], r);
+
+        // 绘制设置按钮
+        self.draw_button_glyph("SETTINGS", center_x, center_y + 20.0, 200.0, 50.0, [0.2, 0.4, 0.6, 1.0], r);
+
+        // 绘制资料页按钮
+        self.draw_button_glyph("PROFILE", center_x, center_y + 80.0, 200.0, 50.0, [0.5, 0.4, 0.2, 1.0], r);
 
         // 绘制退出按钮
-        self.draw_button_glyph("EXIT GAME", center_x, center_y + 50.0, 200.0, 50.0, [0.6, 0.2, 0.2, 1.0], con, g, glyphs);
+        self.draw_button_glyph("EXIT GAME", center_x, center_y + 140.0, 200.0, 50.0, [0.6, 0.2, 0.2, 1.0], r);
+
+        // 当前玩家，点击进入玩家页
+        let player = format!("PLAYER: {}", self.profiles.active());
+        self.draw_button_glyph(&player, center_x, center_y + 200.0, 300.0, 40.0, [0.3, 0.3, 0.3, 1.0], r);
+
+        // 读取存档失败等提示
+        if let Some(ref status) = self.status {
+            self.draw_text_glyph(status, center_x, center_y + 245.0, 16, [1.0, 0.4, 0.4, 1.0], r);
+        }
+    }
+
+    fn draw_settings(&self, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+
+        // 背景
+        r.fill_rect(
+            [0.1, 0.1, 0.1, 1.0],
+            [0.0, 0.0, self.window_width, self.window_height],
+        );
+
+        self.draw_text_glyph("SETTINGS", center_x, self.settings_row_y(0) - 60.0, 40, [1.0, 1.0, 1.0, 1.0], r);
+
+        let rows = self.settings_rows();
+        for (i, text) in rows.iter().enumerate() {
+            self.draw_button_glyph(text, center_x, self.settings_row_y(i), 360.0, 34.0, [0.2, 0.4, 0.6, 1.0], r);
+        }
+
+        self.draw_button_glyph("BACK", center_x, self.settings_row_y(rows.len()) + 16.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
+    }
+
+    // 资料页：跨局累计统计与最近若干局得分的柱状图
+    fn draw_profile(&self, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let gray = [0.6, 0.6, 0.6, 1.0];
+
+        r.fill_rect(
+            [0.1, 0.1, 0.1, 1.0],
+            [0.0, 0.0, self.window_width, self.window_height],
+        );
+        self.draw_text_glyph("PROFILE", center_x, 50.0, 40, white, r);
+
+        let l = &self.lifetime;
+        let games = |m: GameMode| l.games_played[m.index()];
+        let best = |m: GameMode| self.records.best(m).score;
+        let lines = [
+            format!(
+                "GAMES PLAYED: {}  (CLASSIC {} / SPEED {} / SURVIVAL {})",
+                l.total_games(),
+                games(GameMode::Classic),
+                games(GameMode::Speed),
+                games(GameMode::Survival)
+            ),
+            format!("TOTAL TIME: {}    TOTAL FOOD: {}    LONGEST SNAKE: {}", format_duration(l.total_time), l.total_food, l.longest_snake),
+            format!(
+                "BEST SCORES:  CLASSIC {}  SPEED {}  SURVIVAL {}",
+                best(GameMode::Classic),
+                best(GameMode::Speed),
+                best(GameMode::Survival)
+            ),
+            format!("DEATHS:  WALL {}  SELF {}  TIMEOUT {}", l.deaths[0], l.deaths[1], l.deaths[2]),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text_glyph(line, center_x, 100.0 + i as f64 * 28.0, 18, white, r);
+        }
+
+        // 最近得分柱状图，颜色区分模式
+        let chart_w = (self.window_width - 80.0).min(600.0);
+        let chart_left = center_x - chart_w / 2.0;
+        let chart_bottom = self.window_height - 100.0;
+        let chart_h = (chart_bottom - 250.0).max(40.0);
+        self.draw_text_glyph("RECENT SCORES", center_x, chart_bottom - chart_h - 20.0, 16, gray, r);
+        r.fill_rect(gray, [chart_left, chart_bottom, chart_w, 2.0]);
+        let history = &l.score_history;
+        if history.is_empty() {
+            self.draw_text_glyph("NO GAMES YET", center_x, chart_bottom - chart_h / 2.0, 18, gray, r);
+        } else {
+            let max_score = history.iter().map(|(_, s)| *s).max().unwrap_or(0).max(1) as f64;
+            let slot = chart_w / history.len().max(10) as f64;
+            for (i, (mode, score)) in history.iter().enumerate() {
+                let color = match mode {
+                    GameMode::Classic => [0.2, 0.7, 0.3, 1.0],
+                    GameMode::Speed => [0.2, 0.5, 0.9, 1.0],
+                    GameMode::Survival => [0.9, 0.6, 0.2, 1.0],
+                };
+                let h = (*score as f64 / max_score * chart_h).max(2.0);
+                let x = chart_left + i as f64 * slot + slot * 0.15;
+                r.fill_rect(color, [x, chart_bottom - h, slot * 0.7, h]);
+            }
+            self.draw_text_glyph(&format!("MAX {}", max_score as i32), chart_left + 30.0, chart_bottom - chart_h - 20.0, 14, gray, r);
+        }
+        // 图例
+        let legend_y = chart_bottom + 18.0;
+        for (i, (label, color)) in [("CLASSIC", [0.2, 0.7, 0.3, 1.0]), ("SPEED", [0.2, 0.5, 0.9, 1.0]), ("SURVIVAL", [0.9, 0.6, 0.2, 1.0])].iter().enumerate() {
+            let x = center_x - 150.0 + i as f64 * 110.0;
+            r.fill_rect(*color, [x, legend_y - 6.0, 12.0, 12.0]);
+            self.draw_text_glyph(label, x + 50.0, legend_y, 14, gray, r);
+        }
+
+        self.draw_button_glyph("ACHIEVEMENTS", center_x - 110.0, self.window_height - 40.0, 200.0, 40.0, [0.5, 0.4, 0.2, 1.0], r);
+        self.draw_button_glyph("BACK", center_x + 110.0, self.window_height - 40.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
+    }
+
+    // 成就一览：名称、说明、进度条，已解锁的以金色显示
+    fn draw_achievements(&self, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        let gold = [1.0, 0.85, 0.2, 1.0];
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let gray = [0.6, 0.6, 0.6, 1.0];
+
+        r.fill_rect(
+            [0.1, 0.1, 0.1, 1.0],
+            [0.0, 0.0, self.window_width, self.window_height],
+        );
+        self.draw_text_glyph("ACHIEVEMENTS", center_x, 50.0, 40, white, r);
+        let summary = format!("{} / {} UNLOCKED", self.achievements.unlocked_count(), ACHIEVEMENTS.len());
+        self.draw_text_glyph(&summary, center_x, 90.0, 16, gray, r);
+
+        let card_w = (self.window_width - 80.0).min(520.0);
+        let left = center_x - card_w / 2.0;
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            let top = 115.0 + i as f64 * 78.0;
+            let unlocked = self.achievements.is_unlocked(i);
+            let accent = if unlocked { gold } else { gray };
+            r.fill_rect([0.18, 0.18, 0.18, 1.0], [left, top, card_w, 68.0]);
+            r.fill_rect(accent, [left, top, 4.0, 68.0]);
+            self.draw_text_glyph(a.name, left + 110.0, top + 18.0, 20, if unlocked { gold } else { white }, r);
+            self.draw_text_glyph(a.description, left + card_w - 150.0, top + 18.0, 14, gray, r);
+
+            // 进度条
+            let progress = self.achievements.progress(i);
+            let bar_w = card_w - 130.0;
+            let fraction = progress as f64 / a.goal as f64;
+            r.fill_rect([0.0, 0.0, 0.0, 0.6], [left + 20.0, top + 42.0, bar_w, 10.0]);
+            r.fill_rect(accent, [left + 20.0, top + 42.0, bar_w * fraction, 10.0]);
+            let label = if unlocked { "UNLOCKED".to_string() } else { format!("{} / {}", progress, a.goal) };
+            self.draw_text_glyph(&label, left + card_w - 55.0, top + 47.0, 14, accent, r);
+        }
+
+        self.draw_button_glyph("BACK", center_x, self.window_height - 40.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
     }
 
-    fn draw_mode_selection(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    // 玩家页：玩家列表、新建/改名/删除，以及当前玩家的排行榜
+    fn draw_players(&self, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let gray = [0.6, 0.6, 0.6, 1.0];
+
+        r.fill_rect(
+            [0.1, 0.1, 0.1, 1.0],
+            [0.0, 0.0, self.window_width, self.window_height],
+        );
+        self.draw_text_glyph("PLAYERS", center_x, 40.0, 36, white, r);
+
+        for (i, name) in self.profiles.names().iter().enumerate() {
+            let active = name == self.profiles.active();
+            let color = if active { [0.2, 0.6, 0.2, 1.0] } else { [0.2, 0.4, 0.6, 1.0] };
+            let label = if active { format!("> {} <", name) } else { name.clone() };
+            self.draw_button_glyph(&label, center_x, player_row_y(i), 360.0, 34.0, color, r);
+        }
+
+        let buttons_y = player_row_y(MAX_PROFILES) + 10.0;
+        self.draw_button_glyph("NEW", center_x - 160.0, buttons_y, 150.0, 36.0, [0.2, 0.6, 0.2, 1.0], r);
+        self.draw_button_glyph("RENAME", center_x, buttons_y, 150.0, 36.0, [0.5, 0.4, 0.2, 1.0], r);
+        self.draw_button_glyph("DELETE", center_x + 160.0, buttons_y, 150.0, 36.0, [0.6, 0.2, 0.2, 1.0], r);
+        if let Some(ref status) = self.status {
+            self.draw_text_glyph(status, center_x, buttons_y + 34.0, 16, [1.0, 0.4, 0.4, 1.0], r);
+        }
+
+        // 排行榜，每个模式一列
+        let top = buttons_y + 64.0;
+        for (col, mode) in GameMode::ALL.iter().enumerate() {
+            let x = center_x + (col as f64 - 1.0) * 200.0;
+            self.draw_text_glyph(mode.label(), x, top, 16, gray, r);
+            for (i, (name, score)) in self.high_scores.entries(*mode).iter().enumerate() {
+                let line = format!("{}. {}  {}", i + 1, name, score);
+                self.draw_text_glyph(&line, x, top + 22.0 + i as f64 * 20.0, 14, white, r);
+            }
+        }
+
+        self.draw_button_glyph("BACK", center_x, self.window_height - 40.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
+
+        if let Some((purpose, ref input)) = self.text_entry {
+            let prompt = if purpose == TextEntryPurpose::RenamePlayer { "RENAME PLAYER" } else { "NEW PLAYER" };
+            self.draw_text_entry(prompt, input, r);
+        }
+    }
+
+    // 居中的文字输入框
+    fn draw_text_entry(&self, prompt: &str, input: &TextInput, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        let center_y = self.window_height / 2.0;
+        r.fill_rect([0.0, 0.0, 0.0, 0.6], [0.0, 0.0, self.window_width, self.window_height]);
+        r.fill_rect([0.15, 0.15, 0.15, 1.0], [center_x - 180.0, center_y - 60.0, 360.0, 120.0]);
+        self.draw_text_glyph(prompt, center_x, center_y - 35.0, 20, [1.0, 1.0, 1.0, 1.0], r);
+        r.fill_rect([0.0, 0.0, 0.0, 1.0], [center_x - 140.0, center_y - 15.0, 280.0, 34.0]);
+        self.draw_text_glyph(&format!("{}_", input.text), center_x, center_y + 2.0, 20, [1.0, 0.85, 0.2, 1.0], r);
+        self.draw_text_glyph("ENTER: OK   ESC: CANCEL", center_x, center_y + 40.0, 14, [0.6, 0.6, 0.6, 1.0], r);
+    }
+
+    // 按键设置页：每个方向的主按键可点击后重新指定，备用按键固定
+    fn draw_controls(&self, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        r.fill_rect(
+            [0.1, 0.1, 0.1, 1.0],
+            [0.0, 0.0, self.window_width, self.window_height],
+        );
+        self.draw_text_glyph("CONTROLS", center_x, 60.0, 40, [1.0, 1.0, 1.0, 1.0], r);
+        for (i, dir) in DIRECTIONS.iter().enumerate() {
+            let name = direction_key(*dir).to_uppercase();
+            let text = if self.awaiting_key == Some(i) {
+                format!("{}: PRESS A KEY...", name)
+            } else {
+                format!("{}: {} / {}", name, key_name(self.bindings.primary[i]), key_name(self.bindings.secondary[i]))
+            };
+            let color = if self.awaiting_key == Some(i) { [0.5, 0.4, 0.2, 1.0] } else { [0.2, 0.4, 0.6, 1.0] };
+            self.draw_button_glyph(&text, center_x, controls_row_y(i), 360.0, 40.0, color, r);
+        }
+        self.draw_text_glyph("CLICK A ROW, THEN PRESS THE NEW KEY", center_x, controls_row_y(DIRECTIONS.len()), 14, [0.6, 0.6, 0.6, 1.0], r);
+        self.draw_button_glyph("BACK", center_x, self.window_height - 40.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
+    }
+
+    fn draw_mode_selection(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
 
         // 绘制背景
-        rectangle(
+        r.fill_rect(
             [0.1, 0.1, 0.1, 1.0], // 深色背景
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
-        self.draw_text_glyph("SELECT MODE", center_x + 2.0, center_y - 118.0, 40, [0.0, 0.0, 0.0, 0.8], con, g, glyphs);
-        self.draw_text_glyph("SELECT MODE", center_x, center_y - 120.0, 40, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("SELECT MODE", center_x + 2.0, center_y - 118.0, 40, [0.0, 0.0, 0.0, 0.8], r);
+        self.draw_text_glyph("SELECT MODE", center_x, center_y - 120.0, 40, [1.0, 1.0, 1.0, 1.0], r);
 
         // 绘制模式按钮
-        self.draw_button_glyph("CLASSIC", center_x, center_y - 40.0, 200.0, 50.0, [0.2, 0.4, 0.8, 1.0], con, g, glyphs);
-        self.draw_button_glyph("SPEED", center_x, center_y + 20.0, 200.0, 50.0, [0.8, 0.4, 0.2, 1.0], con, g, glyphs);
-        self.draw_button_glyph("SURVIVAL", center_x, center_y + 80.0, 200.0, 50.0, [0.8, 0.2, 0.8, 1.0], con, g, glyphs);
-        self.draw_button_glyph("BACK", center_x, center_y + 140.0, 200.0, 50.0, [0.4, 0.4, 0.4, 1.0], con, g, glyphs);
+        self.draw_button_glyph("CLASSIC", center_x, center_y - 40.0, 200.0, 50.0, [0.2, 0.4, 0.8, 1.0], r);
+        self.draw_button_glyph("SPEED", center_x, center_y + 20.0, 200.0, 50.0, [0.8, 0.4, 0.2, 1.0], r);
+        self.draw_button_glyph("SURVIVAL", center_x, center_y + 80.0, 200.0, 50.0, [0.8, 0.2, 0.8, 1.0], r);
+        self.draw_button_glyph("BACK", center_x, center_y + 140.0, 200.0, 50.0, [0.4, 0.4, 0.4, 1.0], r);
     }
 
-    fn draw_speed_selection(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    fn draw_speed_selection(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
 
         // 背景
-        rectangle(
+        r.fill_rect(
             [0.1, 0.1, 0.1, 1.0],
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
-        self.draw_text_glyph("SELECT SPEED", center_x, center_y - 80.0, 36, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("SELECT SPEED", center_x, center_y - 80.0, 36, [1.0, 1.0, 1.0, 1.0], r);
 
         let slow_color = if self.selected_speed == GameSpeed::Slow { [0.2, 0.8, 0.2, 1.0] } else { [0.2, 0.6, 0.2, 1.0] };
         let medium_color = if self.selected_speed == GameSpeed::Medium { [0.2, 0.8, 0.2, 1.0] } else { [0.2, 0.6, 0.2, 1.0] };
         let fast_color = if self.selected_speed == GameSpeed::Fast { [0.2, 0.8, 0.2, 1.0] } else { [0.2, 0.6, 0.2, 1.0] };
-        self.draw_button_glyph("SLOW", center_x - 120.0, center_y, 120.0, 40.0, slow_color, con, g, glyphs);
-        self.draw_button_glyph("MEDIUM", center_x, center_y, 120.0, 40.0, medium_color, con, g, glyphs);
-        self.draw_button_glyph("FAST", center_x + 120.0, center_y, 120.0, 40.0, fast_color, con, g, glyphs);
+        self.draw_button_glyph("SLOW", center_x - 120.0, center_y, 120.0, 40.0, slow_color, r);
+        self.draw_button_glyph("MEDIUM", center_x, center_y, 120.0, 40.0, medium_color, r);
+        self.draw_button_glyph("FAST", center_x + 120.0, center_y, 120.0, 40.0, fast_color, r);
 
-        self.draw_button_glyph("BACK", center_x, center_y + 100.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], con, g, glyphs);
+        self.draw_button_glyph("BACK", center_x, center_y + 100.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
     }
 
-    fn draw_confirm_start(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    fn draw_confirm_start(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
 
         // 半透明遮罩
-        rectangle(
+        r.fill_rect(
             [0.0, 0.0, 0.0, 0.7],
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
         // 对话框
-        rectangle(
+        r.fill_rect(
             [0.15, 0.15, 0.15, 1.0],
             [center_x - 160.0, center_y - 80.0, 320.0, 160.0],
-            con.transform,
-            g,
         );
 
-        self.draw_text_glyph("Start with:", center_x, center_y - 40.0, 24, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("Start with:", center_x, center_y - 40.0, 24, [1.0, 1.0, 1.0, 1.0], r);
         let speed_text = match self.selected_speed {
             GameSpeed::Slow => "SLOW",
             GameSpeed::Medium => "MEDIUM",
             GameSpeed::Fast => "FAST",
         };
-        self.draw_text_glyph(speed_text, center_x, center_y - 10.0, 28, [1.0, 1.0, 0.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph(speed_text, center_x, center_y - 10.0, 28, [1.0, 1.0, 0.0, 1.0], r);
 
-        self.draw_button_glyph("YES", center_x - 50.0, center_y + 40.0, 80.0, 40.0, [0.2, 0.8, 0.2, 1.0], con, g, glyphs);
-        self.draw_button_glyph("NO", center_x + 50.0, center_y + 40.0, 80.0, 40.0, [0.8, 0.2, 0.2, 1.0], con, g, glyphs);
+        self.draw_button_glyph("YES", center_x - 50.0, center_y + 40.0, 80.0, 40.0, [0.2, 0.8, 0.2, 1.0], r);
+        self.draw_button_glyph("NO", center_x + 50.0, center_y + 40.0, 80.0, 40.0, [0.8, 0.2, 0.2, 1.0], r);
     }
 
-    fn draw_game_menu_button(&self, con: &Context, g: &mut G2d) {
+    fn draw_game_menu_button(&self, r: &mut dyn Renderer) {
         let menu_button_x = self.window_width - 60.0;
         let menu_button_y = 30.0;
         
         // 绘制菜单按钮背景
-        rectangle(
+        r.fill_rect(
             [0.2, 0.2, 0.2, 0.8], // 半透明深色背景
             [menu_button_x - 20.0, menu_button_y - 15.0, 40.0, 30.0],
-            con.transform,
-            g,
         );
         
         // 绘制菜单按钮边框
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0], // 白色边框
             [menu_button_x - 20.0, menu_button_y - 15.0, 2.0, 30.0],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [menu_button_x + 18.0, menu_button_y - 15.0, 2.0, 30.0],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [menu_button_x - 20.0, menu_button_y - 15.0, 40.0, 2.0],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [menu_button_x - 20.0, menu_button_y + 13.0, 40.0, 2.0],
-            con.transform,
-            g,
         );
         
         // 绘制菜单图标（三条横线）
@@ -435,68 +1075,77 @@ impl Menu {
         let line_x2 = menu_button_x + 12.0;
         
         // 第一条线
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [line_x1, line_y1 - 1.0, line_x2 - line_x1, 2.0],
-            con.transform,
-            g,
         );
         // 第二条线
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [line_x1, line_y2 - 1.0, line_x2 - line_x1, 2.0],
-            con.transform,
-            g,
         );
         // 第三条线
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [line_x1, line_y3 - 1.0, line_x2 - line_x1, 2.0],
-            con.transform,
-            g,
         );
     }
     
-    pub fn draw_score(&self, score: i32, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    pub fn draw_score(&self, score: i32, r: &mut dyn Renderer) {
         let score_text = format!("SCORE: {}", score);
-        self.draw_text_glyph(&score_text, 100.0, 30.0, 24, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph(&score_text, 100.0, 30.0, 24, [1.0, 1.0, 1.0, 1.0], r);
     }
 
-    pub fn draw_text_top_right(&self, text: &str, size_px: f64, color: [f32; 4], con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
-        use piston_window::character::CharacterCache;
+    pub fn draw_text_top_right(&self, text: &str, size_px: f64, color: [f32; 4], r: &mut dyn Renderer) {
         let spx = size_px as u32;
-        let mut total_w = 0.0;
-        for ch in text.chars() {
-            if let Ok(g) = glyphs.character(spx, ch) { total_w += g.advance_width(); }
-        }
+        let total_w = r.text_width(text, spx);
         let margin_right = 20.0;
         let x_right = self.window_width - margin_right;
         // 通过中心绘制，计算文本中心点位置
         let x_center = x_right - total_w / 2.0;
         let y = 30.0;
-        self.draw_text_glyph(text, x_center, y, spx, color, con, g, glyphs);
+        self.draw_text_glyph(text, x_center, y, spx, color, r);
     }
     
-    pub fn draw_pause_indicator(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    pub fn draw_pause_indicator(&self, r: &mut dyn Renderer) {
         if self.is_paused {
             let center_x = self.window_width / 2.0;
             let center_y = self.window_height / 2.0;
             
             // 绘制半透明背景
-            rectangle(
+            r.fill_rect(
                 [0.0, 0.0, 0.0, 0.5], // 半透明黑色背景
                 [0.0, 0.0, self.window_width, self.window_height],
-                con.transform,
-                g,
             );
             
             // 绘制暂停文本
-            self.draw_text_glyph("PAUSED", center_x, center_y, 48, [1.0, 1.0, 0.0, 1.0], con, g, glyphs);
-            self.draw_text_glyph("Press ESC to resume", center_x, center_y + 50.0, 22, [0.8, 0.8, 0.8, 1.0], con, g, glyphs);
+            self.draw_text_glyph("PAUSED", center_x, center_y, 48, [1.0, 1.0, 0.0, 1.0], r);
+            self.draw_text_glyph("Press ESC to resume", center_x, center_y + 50.0, 22, [0.8, 0.8, 0.8, 1.0], r);
         }
     }
 
-    pub fn draw_controls_help(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    // 开局与恢复时的 READY / 3-2-1 / GO
+    pub fn draw_countdown(&self, phase: Option<CountdownPhase>, r: &mut dyn Renderer) {
+        let center_x = self.window_width / 2.0;
+        let center_y = self.window_height / 2.0;
+        let (text, color) = match phase {
+            Some(CountdownPhase::Ready) => ("READY".to_string(), [1.0, 1.0, 1.0, 1.0]),
+            Some(CountdownPhase::Count(n)) => (n.to_string(), [1.0, 1.0, 0.0, 1.0]),
+            Some(CountdownPhase::Go) => ("GO!".to_string(), [0.3, 1.0, 0.3, 1.0]),
+            None => return,
+        };
+        // 倒数期间压暗画面，GO 时蛇已在移动，不再遮挡
+        if phase != Some(CountdownPhase::Go) {
+            r.fill_rect(
+                [0.0, 0.0, 0.0, 0.4],
+                [0.0, 0.0, self.window_width, self.window_height],
+            );
+        }
+        self.draw_text_glyph(&text, center_x + 3.0, center_y + 3.0, 96, [0.0, 0.0, 0.0, 0.8], r);
+        self.draw_text_glyph(&text, center_x, center_y, 96, color, r);
+    }
+
+    pub fn draw_controls_help(&self, r: &mut dyn Renderer) {
         // 在游戏区域外（右侧边距）显示操作说明
         let panel_w = 230.0;
         let panel_h = 160.0;
@@ -504,203 +1153,208 @@ impl Menu {
         let y = 60.0;
 
         // 背景面板
-        rectangle(
+        r.fill_rect(
             [0.0, 0.0, 0.0, 0.35],
             [x, y, panel_w, panel_h],
-            con.transform,
-            g,
         );
 
         // 标题与内容
-        self.draw_text_glyph("CONTROLS", x + panel_w / 2.0, y + 22.0, 22, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("CONTROLS", x + panel_w / 2.0, y + 22.0, 22, [1.0, 1.0, 1.0, 1.0], r);
 
         let line1 = "Move: Arrow / WASD";
         let line2 = "Menu: ESC or button";
         let line3 = "Click: UI buttons";
         let mut ty = y + 52.0;
         let lh = 20.0;
-        self.draw_text_glyph(line1, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], con, g, glyphs);
+        self.draw_text_glyph(line1, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], r);
         ty += lh;
-        self.draw_text_glyph(line2, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], con, g, glyphs);
+        self.draw_text_glyph(line2, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], r);
         ty += lh;
-        self.draw_text_glyph(line3, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], con, g, glyphs);
+        self.draw_text_glyph(line3, x + panel_w / 2.0, ty, 18, [0.9, 0.9, 0.9, 1.0], r);
     }
 
-    fn draw_game_menu(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    fn draw_game_menu(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
 
         // 绘制半透明背景
-        rectangle(
+        r.fill_rect(
             [0.0, 0.0, 0.0, 0.7], // 半透明黑色背景
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
         // 绘制菜单背景
-        rectangle(
+        r.fill_rect(
             [0.1, 0.1, 0.1, 0.95], // 深色背景
-            [center_x - 150.0, center_y - 120.0, 300.0, 240.0],
-            con.transform,
-            g,
+            [center_x - 150.0, center_y - 140.0, 300.0, 320.0],
         );
 
         // 绘制菜单边框（更粗的边框）
         let border_width = 3.0;
-        rectangle(
+        r.fill_rect(
             [0.8, 0.8, 0.8, 1.0], // 浅灰色边框
-            [center_x - 150.0, center_y - 120.0, border_width, 240.0],
-            con.transform,
-            g,
+            [center_x - 150.0, center_y - 140.0, border_width, 320.0],
         );
-        rectangle(
+        r.fill_rect(
             [0.8, 0.8, 0.8, 1.0],
-            [center_x + 147.0, center_y - 120.0, border_width, 240.0],
-            con.transform,
-            g,
+            [center_x + 147.0, center_y - 140.0, border_width, 320.0],
         );
-        rectangle(
+        r.fill_rect(
             [0.8, 0.8, 0.8, 1.0],
-            [center_x - 150.0, center_y - 120.0, 300.0, border_width],
-            con.transform,
-            g,
+            [center_x - 150.0, center_y - 140.0, 300.0, border_width],
         );
-        rectangle(
+        r.fill_rect(
             [0.8, 0.8, 0.8, 1.0],
-            [center_x - 150.0, center_y + 117.0, 300.0, border_width],
-            con.transform,
-            g,
+            [center_x - 150.0, center_y + 177.0, 300.0, border_width],
         );
 
         // 绘制菜单标题
-        self.draw_text_glyph("GAME MENU", center_x, center_y - 80.0, 32, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph("GAME MENU", center_x, center_y - 100.0, 32, [1.0, 1.0, 1.0, 1.0], r);
 
         // 绘制菜单按钮
         let pause_text = if self.is_paused { "RESUME" } else { "PAUSE" };
         let pause_color = if self.is_paused { [0.2, 0.8, 0.2, 1.0] } else { [0.8, 0.6, 0.2, 1.0] };
-        self.draw_button_glyph(pause_text, center_x, center_y - 30.0, 200.0, 40.0, pause_color, con, g, glyphs);
-        self.draw_button_glyph("RESTART", center_x, center_y + 20.0, 200.0, 40.0, [0.6, 0.4, 0.2, 1.0], con, g, glyphs);
-        self.draw_button_glyph("MAIN MENU", center_x, center_y + 70.0, 200.0, 40.0, [0.6, 0.2, 0.2, 1.0], con, g, glyphs);
-        self.draw_button_glyph("CLOSE", center_x, center_y + 120.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], con, g, glyphs);
+        self.draw_button_glyph(pause_text, center_x, center_y - 50.0, 200.0, 40.0, pause_color, r);
+        self.draw_button_glyph("RESTART", center_x, center_y, 200.0, 40.0, [0.6, 0.4, 0.2, 1.0], r);
+        let save_color = if self.can_save { [0.2, 0.5, 0.5, 1.0] } else { [0.3, 0.3, 0.3, 1.0] };
+        self.draw_button_glyph("SAVE & QUIT", center_x, center_y + 50.0, 200.0, 40.0, save_color, r);
+        self.draw_button_glyph("MAIN MENU", center_x, center_y + 100.0, 200.0, 40.0, [0.6, 0.2, 0.2, 1.0], r);
+        self.draw_button_glyph("CLOSE", center_x, center_y + 150.0, 200.0, 40.0, [0.4, 0.4, 0.4, 1.0], r);
     }
 
-    fn draw_game_over_menu(&self, con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    fn draw_game_over_menu(&self, r: &mut dyn Renderer) {
         let center_x = self.window_width / 2.0;
         let center_y = self.window_height / 2.0;
+        let (box_w, box_h) = (460.0, 460.0);
+        let (left, top) = (center_x - box_w / 2.0, center_y - box_h / 2.0);
 
         // 绘制半透明背景
-        rectangle(
+        r.fill_rect(
             [0.0, 0.0, 0.0, 0.8], // 半透明黑色背景
             [0.0, 0.0, self.window_width, self.window_height],
-            con.transform,
-            g,
         );
 
         // 绘制菜单背景
-        rectangle(
+        r.fill_rect(
             [0.1, 0.1, 0.1, 0.95], // 深色背景
-            [center_x - 150.0, center_y - 100.0, 300.0, 200.0],
-            con.transform,
-            g,
+            [left, top, box_w, box_h],
         );
 
         // 绘制菜单边框
         let border_width = 3.0;
-        rectangle(
-            [0.8, 0.0, 0.0, 1.0], // 红色边框
-            [center_x - 150.0, center_y - 100.0, border_width, 200.0],
-            con.transform,
-            g,
-        );
-        rectangle(
-            [0.8, 0.0, 0.0, 1.0],
-            [center_x + 147.0, center_y - 100.0, border_width, 200.0],
-            con.transform,
-            g,
-        );
-        rectangle(
-            [0.8, 0.0, 0.0, 1.0],
-            [center_x - 150.0, center_y - 100.0, 300.0, border_width],
-            con.transform,
-            g,
-        );
-        rectangle(
-            [0.8, 0.0, 0.0, 1.0],
-            [center_x - 150.0, center_y + 97.0, 300.0, border_width],
-            con.transform,
-            g,
-        );
+        let border_color = [0.8, 0.0, 0.0, 1.0]; // 红色边框
+        r.fill_rect(border_color, [left, top, border_width, box_h]);
+        r.fill_rect(border_color, [left + box_w - border_width, top, border_width, box_h]);
+        r.fill_rect(border_color, [left, top, box_w, border_width]);
+        r.fill_rect(border_color, [left, top + box_h - border_width, box_w, border_width]);
 
         // 绘制GameOver标题
-        self.draw_text_glyph("GAME OVER", center_x + 2.0, center_y - 58.0, 36, [0.0, 0.0, 0.0, 0.8], con, g, glyphs);
-        self.draw_text_glyph("GAME OVER", center_x, center_y - 60.0, 36, [1.0, 0.0, 0.0, 1.0], con, g, glyphs);
-        
-        // 绘制最终分数
-        let score_text = format!("FINAL SCORE: {}", self.final_score);
-        self.draw_text_glyph(&score_text, center_x, center_y - 20.0, 24, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        let won = self.last_run.as_ref().is_some_and(|r| r.won);
+        let (title, title_color) = if won { ("YOU WIN!", [1.0, 0.8, 0.2, 1.0]) } else { ("GAME OVER", [1.0, 0.0, 0.0, 1.0]) };
+        self.draw_text_glyph(title, center_x + 2.0, center_y - 193.0, 36, [0.0, 0.0, 0.0, 0.8], r);
+        self.draw_text_glyph(title, center_x, center_y - 195.0, 36, title_color, r);
+
+        if let Some(ref run) = self.last_run {
+            self.draw_run_summary(run, center_x, center_y, r);
+        }
 
         // 绘制菜单按钮
-        self.draw_button_glyph("PLAY AGAIN", center_x, center_y + 20.0, 200.0, 40.0, [0.2, 0.8, 0.2, 1.0], con, g, glyphs);
-        self.draw_button_glyph("MAIN MENU", center_x, center_y + 70.0, 200.0, 40.0, [0.6, 0.2, 0.2, 1.0], con, g, glyphs);
+        self.draw_button_glyph("PLAY AGAIN", center_x, center_y + 150.0, 200.0, 40.0, [0.2, 0.8, 0.2, 1.0], r);
+        self.draw_button_glyph("MAIN MENU", center_x, center_y + 200.0, 200.0, 40.0, [0.6, 0.2, 0.2, 1.0], r);
+
+        // 进入排行榜：输入名字（默认为当前玩家名）
+        if let Some((TextEntryPurpose::HighScoreName, ref input)) = self.text_entry {
+            self.draw_text_entry("NEW HIGH SCORE! NAME:", input, r);
+        }
     }
 
-    fn draw_button_glyph(&self, text: &str, x: f64, y: f64, width: f64, height: f64, color: [f32; 4], con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
+    // 结算表格：本局数据与此前最好成绩对比，打破纪录的项用金色显示
+    fn draw_run_summary(&self, run: &RunStats, center_x: f64, center_y: f64, r: &mut dyn Renderer) {
+        let best = &self.previous_best;
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let gray = [0.6, 0.6, 0.6, 1.0];
+        let gold = [1.0, 0.8, 0.2, 1.0];
+
+        let subtitle = if run.score > best.score {
+            format!("{} MODE - NEW BEST!", run.mode.label())
+        } else {
+            format!("{} MODE", run.mode.label())
+        };
+        let subtitle_color = if run.score > best.score { gold } else { white };
+        self.draw_text_glyph(&subtitle, center_x, center_y - 160.0, 18, subtitle_color, r);
+
+        // (名称, 本局, 最好成绩, 是否破纪录)
+        let mut rows: Vec<(&str, String, String, bool)> = vec![
+            ("SCORE", run.score.to_string(), best.score.to_string(), run.score > best.score),
+            ("TIME", format_duration(run.duration), format_duration(best.duration), run.duration > best.duration),
+            ("MAX LENGTH", run.max_length.to_string(), best.max_length.to_string(), run.max_length > best.max_length),
+            ("FOOD EATEN", (run.foods + run.big_foods).to_string(), best.foods.to_string(), run.foods + run.big_foods > best.foods),
+            ("BIG FOOD", format!("{} / {} MISSED", run.big_foods, run.big_foods_missed), "-".to_string(), false),
+            ("TURNS", run.turns.to_string(), "-".to_string(), false),
+            ("AVG SPEED", format!("{:.1} /S", run.average_speed()), "-".to_string(), false),
+        ];
+        if run.mode == GameMode::Survival {
+            rows.push(("TIME ADDED", format!("+{}S", run.time_added), "-".to_string(), false));
+        }
+        rows.push(("MAX COMBO", run.max_combo.to_string(), best.max_combo.to_string(), run.max_combo > best.max_combo));
+        let ending = if run.won { "WON" } else { run.death.map_or("-", |d| d.label()) };
+        rows.push(("DEATH", ending.to_string(), "-".to_string(), false));
+
+        let (label_x, run_x, best_x) = (center_x - 120.0, center_x + 50.0, center_x + 160.0);
+        let header_y = center_y - 128.0;
+        self.draw_text_glyph("THIS RUN", run_x, header_y, 14, gray, r);
+        self.draw_text_glyph("BEST", best_x, header_y, 14, gray, r);
+        for (i, (label, value, best_value, record)) in rows.iter().enumerate() {
+            let y = header_y + 26.0 + i as f64 * 22.0;
+            self.draw_text_glyph(label, label_x, y, 16, gray, r);
+            self.draw_text_glyph(value, run_x, y, 16, if *record { gold } else { white }, r);
+            self.draw_text_glyph(best_value, best_x, y, 16, gray, r);
+        }
+    }
+
+    fn draw_button_glyph(&self, text: &str, x: f64, y: f64, width: f64, height: f64, color: [f32; 4], r: &mut dyn Renderer) {
         // 绘制按钮背景
-        rectangle(
+        r.fill_rect(
             color,
             [x - width/2.0, y - height/2.0, width, height],
-            con.transform,
-            g,
         );
 
         // 绘制按钮边框（细边框）
         let border_width = 2.0;
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0], // 白色边框
             [x - width/2.0, y - height/2.0, border_width, height],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [x + width/2.0 - border_width, y - height/2.0, border_width, height],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [x - width/2.0, y - height/2.0, width, border_width],
-            con.transform,
-            g,
         );
-        rectangle(
+        r.fill_rect(
             [1.0, 1.0, 1.0, 1.0],
             [x - width/2.0, y + height/2.0 - border_width, width, border_width],
-            con.transform,
-            g,
         );
 
         // 绘制按钮文字（使用字体）
-        self.draw_text_glyph(text, x + 1.0, y + 1.0, 22, [0.0, 0.0, 0.0, 0.8], con, g, glyphs);
-        self.draw_text_glyph(text, x, y, 22, [1.0, 1.0, 1.0, 1.0], con, g, glyphs);
+        self.draw_text_glyph(text, x + 1.0, y + 1.0, 22, [0.0, 0.0, 0.0, 0.8], r);
+        self.draw_text_glyph(text, x, y, 22, [1.0, 1.0, 1.0, 1.0], r);
     }
 
-    fn draw_text_glyph(&self, text: &str, x: f64, y: f64, size_px: u32, color: [f32; 4], con: &Context, g: &mut G2d, glyphs: &mut piston_window::Glyphs) {
-        use piston_window::character::CharacterCache;
-        let total_w = glyphs.width(size_px, text).unwrap_or(0.0);
+    fn draw_text_glyph(&self, text: &str, x: f64, y: f64, size_px: u32, color: [f32; 4], r: &mut dyn Renderer) {
+        let total_w = r.text_width(text, size_px);
         let baseline_adjust = (size_px as f64) * 0.35;
-        let transform = con.transform.trans(x - total_w / 2.0, y + baseline_adjust);
-        let txt = piston_window::Text::new_color(color, size_px);
-        let _ = txt.draw(text, glyphs, &con.draw_state, transform, g);
+        r.draw_text(text, size_px, color, [x - total_w / 2.0, y + baseline_adjust]);
     }
 
-    fn draw_text(&self, text: &str, x: f64, y: f64, size: f64, color: [f32; 4], con: &Context, g: &mut G2d) {
+    fn draw_text(&self, text: &str, x: f64, y: f64, size: f64, color: [f32; 4], r: &mut dyn Renderer) {
         // 使用简单的英文文本显示
-        self.draw_english_text(text, x, y, size, color, con, g);
+        self.draw_english_text(text, x, y, size, color, r);
     }
 
-    fn draw_english_text(&self, text: &str, x: f64, y: f64, size: f64, color: [f32; 4], con: &Context, g: &mut G2d) {
+    fn draw_english_text(&self, text: &str, x: f64, y: f64, size: f64, color: [f32; 4], r: &mut dyn Renderer) {
         let char_width = size * 0.6; // 增加字符宽度，让文字更清晰
         let char_height = size;
         let start_x = x - (text.len() as f64 * char_width) / 2.0;
@@ -709,11 +1363,11 @@ impl Menu {
             let char_x = start_x + i as f64 * char_width;
             // 调整字符的垂直位置，让文字更好地居中
             let char_y = y - char_height * 0.1;
-            self.draw_english_char(ch, char_x, char_y, char_width, char_height, color, con, g);
+            self.draw_english_char(ch, char_x, char_y, char_width, char_height, color, r);
         }
     }
 
-    fn draw_english_char(&self, ch: char, x: f64, y: f64, width: f64, height: f64, color: [f32; 4], con: &Context, g: &mut G2d) {
+    fn draw_english_char(&self, ch: char, x: f64, y: f64, width: f64, height: f64, color: [f32; 4], r: &mut dyn Renderer) {
         // 使用简单的点阵字体显示英文字符，增加像素大小以提高清晰度
         let pixel_size = width / 4.0; // 改为4x6像素网格，像素更大更清晰
         
@@ -727,7 +1381,7 @@ impl Menu {
                     [1,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'N' => {
                 let pattern = [
@@ -738,7 +1392,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'A' => {
                 let pattern = [
@@ -749,7 +1403,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'K' => {
                 let pattern = [
@@ -760,7 +1414,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'E' => {
                 let pattern = [
@@ -771,7 +1425,7 @@ impl Menu {
                     [1,1,1,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'G' => {
                 let pattern = [
@@ -782,7 +1436,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'M' => {
                 let pattern = [
@@ -793,7 +1447,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'T' => {
                 let pattern = [
@@ -804,7 +1458,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'R' => {
                 let pattern = [
@@ -815,7 +1469,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'I' => {
                 let pattern = [
@@ -826,7 +1480,7 @@ impl Menu {
                     [1,1,1,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'X' => {
                 let pattern = [
@@ -837,7 +1491,7 @@ impl Menu {
                     [1,0,0,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'C' => {
                 let pattern = [
@@ -848,7 +1502,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'L' => {
                 let pattern = [
@@ -859,7 +1513,7 @@ impl Menu {
                     [1,1,1,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'D' => {
                 let pattern = [
@@ -870,7 +1524,7 @@ impl Menu {
                     [1,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'P' => {
                 let pattern = [
@@ -881,7 +1535,7 @@ impl Menu {
                     [1,0,0,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'U' => {
                 let pattern = [
@@ -892,7 +1546,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'V' => {
                 let pattern = [
@@ -903,7 +1557,7 @@ impl Menu {
                     [0,0,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             'B' => {
                 let pattern = [
@@ -914,7 +1568,7 @@ impl Menu {
                     [1,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '0' => {
                 let pattern = [
@@ -925,7 +1579,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '1' => {
                 let pattern = [
@@ -936,7 +1590,7 @@ impl Menu {
                     [1,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '2' => {
                 let pattern = [
@@ -947,7 +1601,7 @@ impl Menu {
                     [1,1,1,1],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '3' => {
                 let pattern = [
@@ -958,7 +1612,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '4' => {
                 let pattern = [
@@ -969,7 +1623,7 @@ impl Menu {
                     [0,0,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '5' => {
                 let pattern = [
@@ -980,7 +1634,7 @@ impl Menu {
                     [1,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '6' => {
                 let pattern = [
@@ -991,7 +1645,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '7' => {
                 let pattern = [
@@ -1002,7 +1656,7 @@ impl Menu {
                     [1,0,0,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '8' => {
                 let pattern = [
@@ -1013,7 +1667,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             '9' => {
                 let pattern = [
@@ -1024,7 +1678,7 @@ impl Menu {
                     [0,1,1,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             ':' => {
                 let pattern = [
@@ -1035,24 +1689,22 @@ impl Menu {
                     [0,0,0,0],
                     [0,0,0,0],
                 ];
-                self.draw_pattern(pattern, x, y, pixel_size, color, con, g);
+                self.draw_pattern(pattern, x, y, pixel_size, color, r);
             },
             ' ' => {
                 // 空格字符，不绘制任何内容
             },
             _ => {
                 // 对于其他字符，绘制一个简单的方块
-                rectangle(
+                r.fill_rect(
                     color,
                     [x, y - height/2.0, width * 0.8, height],
-                    con.transform,
-                    g,
                 );
             }
         }
     }
 
-    fn draw_pattern(&self, pattern: [[u8; 4]; 6], x: f64, y: f64, pixel_size: f64, color: [f32; 4], con: &Context, g: &mut G2d) {
+    fn draw_pattern(&self, pattern: [[u8; 4]; 6], x: f64, y: f64, pixel_size: f64, color: [f32; 4], r: &mut dyn Renderer) {
         for (row, row_data) in pattern.iter().enumerate() {
             for (col, &pixel) in row_data.iter().enumerate() {
                 if pixel == 1 {
@@ -1060,11 +1712,9 @@ impl Menu {
                     let pixel_y = y - 2.5 * pixel_size + row as f64 * pixel_size;
                     // 增加像素大小，让文字更清晰
                     let pixel_size_with_padding = pixel_size * 1.1;
-                    rectangle(
+                    r.fill_rect(
                         color,
                         [pixel_x - 0.05 * pixel_size, pixel_y - 0.05 * pixel_size, pixel_size_with_padding, pixel_size_with_padding],
-                        con.transform,
-                        g,
                     );
                 }
             }
@@ -1073,7 +1723,7 @@ impl Menu {
 }
 
 // 增加供其他模块使用的简单文本显示
-pub fn draw_simple_text(text: &str, x: f64, y: f64, size: f64, color: [f32; 4], con: &Context, g: &mut G2d) {
+pub fn draw_simple_text(text: &str, x: f64, y: f64, size: f64, color: [f32; 4], r: &mut dyn Renderer) {
     // 用 menu 中自带的字体渲染
     // 这里只调用 draw_english_text，即原 draw_text 的底层
     let char_width = size * 0.6;
@@ -1082,7 +1732,22 @@ pub fn draw_simple_text(text: &str, x: f64, y: f64, size: f64, color: [f32; 4],
     for (i, ch) in text.chars().enumerate() {
         let char_x = start_x + i as f64 * char_width;
         let char_y = y - char_height * 0.1;
-        Menu { state: crate::menu::MenuState::Main, selected_mode: crate::menu::GameMode::Classic, selected_speed: crate::menu::GameSpeed::Medium, font: None, window_width: 800.0, window_height: 600.0, is_paused: false, should_restart: false, final_score: 0 }
-            .draw_english_char(ch, char_x, char_y, char_width, char_height, color, con, g);
+        Menu::new(800.0, 600.0)
+            .draw_english_char(ch, char_x, char_y, char_width, char_height, color, r);
     }
 }
+
+// 玩家页第 i 行的纵坐标
+fn player_row_y(index: usize) -> f64 {
+    90.0 + index as f64 * 40.0
+}
+
+// 按键设置页第 i 行的纵坐标
+fn controls_row_y(index: usize) -> f64 {
+    130.0 + index as f64 * 56.0
+}
+
+// 判断点击位置是否落在以 (cx, cy) 为中心、宽 w 高 h 的按钮内
+fn in_button(x: f64, y: f64, cx: f64, cy: f64, w: f64, h: f64) -> bool {
+    x >= cx - w / 2.0 && x <= cx + w / 2.0 && y >= cy - h / 2.0 && y <= cy + h / 2.0
+}
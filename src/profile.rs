@@ -0,0 +1,210 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::achievements::ACHIEVEMENTS_FILE;
+use crate::settings::SETTINGS_FILE;
+use crate::stats::{LIFETIME_FILE, RECORDS_FILE};
+
+pub const PROFILES_DIR: &str = "profiles";
+pub const DEFAULT_PROFILE: &str = "PLAYER";
+pub const MAX_PROFILES: usize = 6;
+pub const MAX_NAME_LEN: usize = 12;
+const ACTIVE_FILE: &str = "active"; // 记录当前玩家名
+
+#[derive(Debug)]
+pub enum ProfileError {
+    InvalidName,
+    NameTaken,
+    TooMany,
+    LastProfile, // 至少保留一个玩家
+    NotFound,
+    Io(io::Error),
+}
+
+impl ProfileError {
+    // 显示在界面上的提示（界面字体只支持英文）
+    pub fn label(&self) -> String {
+        match self {
+            ProfileError::InvalidName => "INVALID NAME".to_string(),
+            ProfileError::NameTaken => "NAME ALREADY EXISTS".to_string(),
+            ProfileError::TooMany => "TOO MANY PLAYERS".to_string(),
+            ProfileError::LastProfile => "CANNOT DELETE THE LAST PLAYER".to_string(),
+            ProfileError::NotFound => "PLAYER NOT FOUND".to_string(),
+            ProfileError::Io(e) => format!("CANNOT WRITE PLAYER FILES ({})", e).to_uppercase(),
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(e: io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+// 本地玩家。每个玩家一个目录，保存各自的设置、按键、成绩、排行榜、统计与成就
+pub struct Profiles {
+    root: PathBuf,
+    names: Vec<String>,
+    active: String,
+}
+
+impl Default for Profiles {
+    // 尚未从磁盘读取时的占位：只有默认玩家
+    fn default() -> Self {
+        Profiles {
+            root: PathBuf::from(PROFILES_DIR),
+            names: vec![DEFAULT_PROFILE.to_string()],
+            active: DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+impl Profiles {
+    // 读取玩家列表；第一次运行时创建默认玩家，并把旧版本放在根目录的文件移进去
+    pub fn load<P: AsRef<Path>>(root: P) -> Profiles {
+        let root = root.as_ref().to_path_buf();
+        let mut profiles = Profiles {
+            root,
+            names: Vec::new(),
+            active: String::new(),
+        };
+        profiles.scan();
+        if profiles.names.is_empty() {
+            if let Err(e) = profiles.create(DEFAULT_PROFILE) {
+                eprintln!("无法创建玩家: {:?}", e);
+            }
+            for file in [SETTINGS_FILE, RECORDS_FILE, LIFETIME_FILE, ACHIEVEMENTS_FILE].iter() {
+                if Path::new(file).exists() {
+                    let _ = fs::rename(file, profiles.root.join(DEFAULT_PROFILE).join(file));
+                }
+            }
+        }
+        let saved = fs::read_to_string(profiles.root.join(ACTIVE_FILE)).unwrap_or_default();
+        profiles.active = if profiles.names.iter().any(|n| n == saved.trim()) {
+            saved.trim().to_string()
+        } else {
+            profiles.names.first().cloned().unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+        };
+        profiles
+    }
+
+    fn scan(&mut self) {
+        self.names = fs::read_dir(&self.root)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_dir())
+                    .filter_map(|e| e.file_name().into_string().ok())
+                    .filter(|n| is_valid_name(n))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.names.sort();
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn active(&self) -> &str {
+        &self.active
+    }
+
+    // 当前玩家目录下的文件路径
+    pub fn path(&self, file: &str) -> PathBuf {
+        self.root.join(&self.active).join(file)
+    }
+
+    pub fn select(&mut self, name: &str) -> Result<(), ProfileError> {
+        if !self.names.iter().any(|n| n == name) {
+            return Err(ProfileError::NotFound);
+        }
+        self.active = name.to_string();
+        fs::write(self.root.join(ACTIVE_FILE), &self.active)?;
+        Ok(())
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<(), ProfileError> {
+        self.check_new_name(name)?;
+        if self.names.len() >= MAX_PROFILES {
+            return Err(ProfileError::TooMany);
+        }
+        fs::create_dir_all(self.root.join(name))?;
+        self.scan();
+        self.select(name)
+    }
+
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), ProfileError> {
+        self.check_new_name(new)?;
+        fs::rename(self.root.join(old), self.root.join(new))?;
+        self.scan();
+        if self.active == old {
+            self.select(new)?;
+        }
+        Ok(())
+    }
+
+    // 删除玩家；至少保留一个。删除当前玩家后切换到列表中的第一个
+    pub fn delete(&mut self, name: &str) -> Result<(), ProfileError> {
+        if self.names.len() <= 1 {
+            return Err(ProfileError::LastProfile);
+        }
+        fs::remove_dir_all(self.root.join(name))?;
+        self.scan();
+        if self.active == name {
+            let first = self.names[0].clone();
+            self.select(&first)?;
+        }
+        Ok(())
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<(), ProfileError> {
+        if !is_valid_name(name) {
+            return Err(ProfileError::InvalidName);
+        }
+        if self.names.iter().any(|n| n == name) {
+            return Err(ProfileError::NameTaken);
+        }
+        Ok(())
+    }
+}
+
+// 名字只允许字母、数字、下划线和连字符，同时也是目录名
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+// 键盘输入的一行文字（玩家名等）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextInput {
+    pub text: String,
+    pub max_len: usize,
+}
+
+impl TextInput {
+    pub fn new(initial: &str, max_len: usize) -> TextInput {
+        TextInput {
+            text: initial.chars().take(max_len).collect(),
+            max_len,
+        }
+    }
+
+    // 追加文字事件中的可用字符
+    pub fn push_str(&mut self, s: &str) {
+        for c in s.chars() {
+            if self.text.chars().count() >= self.max_len {
+                break;
+            }
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                self.text.push(c);
+            }
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+}
@@ -0,0 +1,354 @@
+use std::fmt;
+use std::path::Path;
+
+use piston_window::types::Color;
+use piston_window::{Context, G2d, Glyphs, Rectangle, Text, Transformed};
+use rusttype::{point, Font, Scale};
+
+pub const FONT_BYTES: &[u8] = include_bytes!("../assets/FiraSans-Regular.ttf");
+pub const MAX_OUTPUT_SIDE: u32 = 8192; // 离屏画面的最大边长，8192x8192 的 RGBA 约 256 MB
+
+// 矩形 [x, y, 宽, 高]，单位为像素
+pub type Rect = [f64; 4];
+
+// 平移加等比缩放；push_transform 时与当前变换叠加
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub offset: [f64; 2],
+    pub scale: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform { offset: [0.0, 0.0], scale: 1.0 };
+
+    // 先做 inner 再做 self
+    fn then(self, inner: Transform) -> Transform {
+        Transform {
+            offset: [self.offset[0] + inner.offset[0] * self.scale, self.offset[1] + inner.offset[1] * self.scale],
+            scale: self.scale * inner.scale,
+        }
+    }
+
+    fn apply_point(self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [self.offset[0] + x * self.scale, self.offset[1] + y * self.scale]
+    }
+
+    fn apply_rect(self, [x, y, w, h]: Rect) -> Rect {
+        let [x, y] = self.apply_point([x, y]);
+        [x, y, w * self.scale, h * self.scale]
+    }
+}
+
+// 界面的所有绘制都经过它：窗口里画到 GPU，离屏时画到内存或只记录调用
+pub trait Renderer {
+    fn clear(&mut self, color: Color);
+    fn fill_rect(&mut self, color: Color, rect: Rect);
+    // pos 为第一个字符的基线左端，与 piston 的 Text 一致
+    fn draw_text(&mut self, text: &str, size: u32, color: Color, pos: [f64; 2]);
+    fn text_width(&mut self, text: &str, size: u32) -> f64;
+    fn push_transform(&mut self, transform: Transform);
+    fn pop_transform(&mut self);
+    // 之后的绘制只落在 clip 内（当前变换下的坐标），None 取消裁剪
+    fn set_clip(&mut self, clip: Option<Rect>);
+}
+
+// 变换栈，栈顶是叠加后的当前变换
+#[derive(Debug, Clone)]
+struct TransformStack(Vec<Transform>);
+
+impl TransformStack {
+    fn new() -> TransformStack {
+        TransformStack(vec![Transform::IDENTITY])
+    }
+
+    fn current(&self) -> Transform {
+        *self.0.last().unwrap_or(&Transform::IDENTITY)
+    }
+
+    fn push(&mut self, transform: Transform) {
+        let combined = self.current().then(transform);
+        self.0.push(combined);
+    }
+
+    fn pop(&mut self) {
+        if self.0.len() > 1 {
+            self.0.pop();
+        }
+    }
+}
+
+// 窗口绘制：draw_2d 回调里构造
+pub struct PistonRenderer<'a, 'b> {
+    con: Context,
+    g: &'a mut G2d<'b>,
+    glyphs: &'a mut Glyphs,
+    transforms: TransformStack,
+}
+
+impl<'a, 'b> PistonRenderer<'a, 'b> {
+    pub fn new(con: Context, g: &'a mut G2d<'b>, glyphs: &'a mut Glyphs) -> PistonRenderer<'a, 'b> {
+        PistonRenderer { con, g, glyphs, transforms: TransformStack::new() }
+    }
+}
+
+impl Renderer for PistonRenderer<'_, '_> {
+    fn clear(&mut self, color: Color) {
+        piston_window::clear(color, self.g);
+    }
+
+    fn fill_rect(&mut self, color: Color, rect: Rect) {
+        let rect = self.transforms.current().apply_rect(rect);
+        Rectangle::new(color).draw(rect, &self.con.draw_state, self.con.transform, self.g);
+    }
+
+    fn draw_text(&mut self, text: &str, size: u32, color: Color, pos: [f64; 2]) {
+        let t = self.transforms.current();
+        let [x, y] = t.apply_point(pos);
+        let size = (size as f64 * t.scale).round() as u32;
+        let transform = self.con.transform.trans(x, y);
+        let _ = Text::new_color(color, size).draw(text, self.glyphs, &self.con.draw_state, transform, self.g);
+    }
+
+    fn text_width(&mut self, text: &str, size: u32) -> f64 {
+        use piston_window::character::CharacterCache;
+        self.glyphs.width(size, text).unwrap_or(0.0)
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.transforms.push(transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        self.con.draw_state.scissor = clip.map(|rect| {
+            // 裁剪矩形以帧缓冲像素为单位，高 DPI 屏幕上与窗口坐标不同
+            let ratio = self.con.viewport.map_or(1.0, |v| v.draw_size[0] as f64 / v.window_size[0].max(1.0));
+            let [x, y, w, h] = self.transforms.current().apply_rect(rect);
+            let px = |v: f64| (v * ratio).max(0.0).round() as u32;
+            [px(x), px(y), px(w), px(h)]
+        });
+    }
+}
+
+// 纯 CPU 的光栅化，画到 RGBA 缓冲区；矩形边缘按覆盖面积抗锯齿
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    font: Font<'static>,
+    transforms: TransformStack,
+    clip: [f64; 4], // 像素坐标下的 [左, 上, 右, 下]
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> SoftwareRenderer {
+        SoftwareRenderer {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            font: Font::try_from_bytes(FONT_BYTES).expect("内置字体无效"),
+            transforms: TransformStack::new(),
+            clip: [0.0, 0.0, width as f64, height as f64],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // 逐行的 RGBA 像素
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    // 不论扩展名，总是写成 PNG
+    pub fn save_png(&self, path: &Path) -> image::ImageResult<()> {
+        image::save_buffer_with_format(path, &self.pixels, self.width, self.height, image::ColorType::Rgba8, image::ImageFormat::Png)
+    }
+
+    // 以 coverage（0~1）为权重把 color 混合到 (x, y)
+    fn blend(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        let [left, top, right, bottom] = self.clip;
+        let (fx, fy) = (x as f64, y as f64);
+        if fx < left.floor() || fy < top.floor() || fx >= right.ceil() || fy >= bottom.ceil() {
+            return;
+        }
+        let a = color[3] * coverage.clamp(0.0, 1.0);
+        if a <= 0.0 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &mut self.pixels[i..i + 4];
+        for c in 0..3 {
+            let dst = pixel[c] as f32 / 255.0;
+            pixel[c] = ((color[c] * a + dst * (1.0 - a)) * 255.0).round() as u8;
+        }
+        let dst_a = pixel[3] as f32 / 255.0;
+        pixel[3] = ((a + dst_a * (1.0 - a)) * 255.0).round() as u8;
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn clear(&mut self, color: Color) {
+        let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let rgba = [c(color[0]), c(color[1]), c(color[2]), c(color[3])];
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+
+    fn fill_rect(&mut self, color: Color, rect: Rect) {
+        let [x, y, w, h] = self.transforms.current().apply_rect(rect);
+        let [cl, ct, cr, cb] = self.clip;
+        let (left, top) = (x.max(cl).max(0.0), y.max(ct).max(0.0));
+        let (right, bottom) = ((x + w).min(cr).min(self.width as f64), (y + h).min(cb).min(self.height as f64));
+        if right <= left || bottom <= top {
+            return;
+        }
+        for py in top.floor() as i32..bottom.ceil() as i32 {
+            let cover_y = (bottom.min(py as f64 + 1.0) - top.max(py as f64)) as f32;
+            for px in left.floor() as i32..right.ceil() as i32 {
+                let cover_x = (right.min(px as f64 + 1.0) - left.max(px as f64)) as f32;
+                self.blend(px, py, color, cover_x * cover_y);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, size: u32, color: Color, pos: [f64; 2]) {
+        let t = self.transforms.current();
+        let [x, y] = t.apply_point(pos);
+        let scale = Scale::uniform((size as f64 * t.scale).round() as f32);
+        // 与 piston 一样逐字前进，不做字距调整
+        let mut pen = x as f32;
+        let glyphs: Vec<_> = text
+            .chars()
+            .map(|ch| {
+                let glyph = self.font.glyph(ch).scaled(scale);
+                let advance = glyph.h_metrics().advance_width;
+                let positioned = glyph.positioned(point(pen, y as f32));
+                pen += advance;
+                positioned
+            })
+            .collect();
+        for glyph in glyphs {
+            if let Some(bb) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, v| self.blend(bb.min.x + gx as i32, bb.min.y + gy as i32, color, v));
+            }
+        }
+    }
+
+    fn text_width(&mut self, text: &str, size: u32) -> f64 {
+        let scale = Scale::uniform(size as f32);
+        text.chars().map(|ch| self.font.glyph(ch).scaled(scale).h_metrics().advance_width as f64).sum()
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.transforms.push(transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        let (w, h) = (self.width as f64, self.height as f64);
+        self.clip = match clip {
+            Some(rect) => {
+                let [x, y, cw, ch] = self.transforms.current().apply_rect(rect);
+                [x.max(0.0), y.max(0.0), (x + cw).min(w), (y + ch).min(h)]
+            }
+            None => [0.0, 0.0, w, h],
+        };
+    }
+}
+
+// 一次绘制调用，按原样记下（不做变换和裁剪）
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Clear(Color),
+    FillRect(Color, Rect),
+    Text { text: String, size: u32, color: Color, pos: [f64; 2] },
+    PushTransform(Transform),
+    PopTransform,
+    SetClip(Option<Rect>),
+}
+
+fn hex(color: Color) -> String {
+    let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}{:02x}", c(color[0]), c(color[1]), c(color[2]), c(color[3]))
+}
+
+impl fmt::Display for DrawCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawCall::Clear(color) => write!(f, "clear {}", hex(*color)),
+            DrawCall::FillRect(color, [x, y, w, h]) => write!(f, "rect {} {:.1} {:.1} {:.1} {:.1}", hex(*color), x, y, w, h),
+            DrawCall::Text { text, size, color, pos } => {
+                write!(f, "text {} {} {:.1} {:.1} {:?}", hex(*color), size, pos[0], pos[1], text)
+            }
+            DrawCall::PushTransform(t) => write!(f, "push {:.1} {:.1} {:.3}", t.offset[0], t.offset[1], t.scale),
+            DrawCall::PopTransform => write!(f, "pop"),
+            DrawCall::SetClip(Some([x, y, w, h])) => write!(f, "clip {:.1} {:.1} {:.1} {:.1}", x, y, w, h),
+            DrawCall::SetClip(None) => write!(f, "clip none"),
+        }
+    }
+}
+
+// 只记录绘制调用，输出为每行一条的文本，便于与保存的结果逐行比较
+#[derive(Debug, Clone, Default)]
+pub struct RecordingRenderer {
+    pub calls: Vec<DrawCall>,
+}
+
+impl RecordingRenderer {
+    pub fn new() -> RecordingRenderer {
+        RecordingRenderer::default()
+    }
+}
+
+impl fmt::Display for RecordingRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for call in &self.calls {
+            writeln!(f, "{}", call)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn clear(&mut self, color: Color) {
+        self.calls.push(DrawCall::Clear(color));
+    }
+
+    fn fill_rect(&mut self, color: Color, rect: Rect) {
+        self.calls.push(DrawCall::FillRect(color, rect));
+    }
+
+    fn draw_text(&mut self, text: &str, size: u32, color: Color, pos: [f64; 2]) {
+        self.calls.push(DrawCall::Text { text: text.to_string(), size, color, pos });
+    }
+
+    // 不加载字体，按每个字符半个字号估算，结果与字体无关
+    fn text_width(&mut self, text: &str, size: u32) -> f64 {
+        text.chars().count() as f64 * size as f64 * 0.5
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.calls.push(DrawCall::PushTransform(transform));
+    }
+
+    fn pop_transform(&mut self) {
+        self.calls.push(DrawCall::PopTransform);
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        self.calls.push(DrawCall::SetClip(clip));
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::{Game, Level};
+use crate::keybindings::{direction_key, parse_direction};
+use crate::menu::{GameMode, GameSpeed};
+use crate::snake::Direction;
+
+// 回放格式版本，步骤含义变化时加一
+pub const REPLAY_VERSION: u32 = 1;
+const HEADER: &str = "snake-replay";
+
+// 对游戏的一次操作。游戏只受这三种操作影响，按顺序重放即可得到同一局
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayStep {
+    Update(f64),           // 推进一帧，值为 dt
+    Turn(Direction),       // 按下方向键
+    Countdown(u32, bool),  // 开始倒数：秒数、是否先显示 READY
+}
+
+impl ReplayStep {
+    pub fn apply(self, game: &mut Game) {
+        match self {
+            ReplayStep::Update(dt) => game.update(dt),
+            ReplayStep::Turn(d) => game.turn(d),
+            ReplayStep::Countdown(seconds, ready) => game.start_countdown(seconds, ready),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    BadHeader,
+    Version(u32),
+    Invalid(usize), // 第几行无法解析
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{}", e),
+            ReplayError::BadHeader => write!(f, "不是回放文件"),
+            ReplayError::Version(v) => write!(f, "不支持的回放版本 {}", v),
+            ReplayError::Invalid(line) => write!(f, "第 {} 行无效", line),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+// 一局的回放：开局参数、随机数种子和全部操作
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub mode: GameMode,
+    pub speed: GameSpeed,
+    pub level: Level,
+    pub seed: u64,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    pub fn new(mode: GameMode, speed: GameSpeed, level: Level, seed: u64) -> Replay {
+        Replay {
+            mode,
+            speed,
+            level,
+            seed,
+            steps: Vec::new(),
+        }
+    }
+
+    // 按回放的开局参数创建游戏，尚未执行任何步骤
+    pub fn start(&self) -> Game {
+        let (w, h) = self.level.board_size();
+        Game::new_seeded(w, h, self.mode, self.speed, self.seed)
+    }
+
+    // 文件格式：头部一行，随后是 key=value 的开局参数，再往后每行一个步骤：
+    // u <dt> / t <方向> / c <秒数> <ready>
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = format!("{} {}\n", HEADER, REPLAY_VERSION);
+        out.push_str(&format!("mode={}\n", self.mode.key()));
+        out.push_str(&format!("speed={}\n", self.speed.key()));
+        out.push_str(&format!("level={}\n", self.level.key()));
+        out.push_str(&format!("seed={}\n", self.seed));
+        for step in &self.steps {
+            match step {
+                ReplayStep::Update(dt) => out.push_str(&format!("u {}\n", dt)),
+                ReplayStep::Turn(d) => out.push_str(&format!("t {}\n", direction_key(*d))),
+                ReplayStep::Countdown(seconds, ready) => out.push_str(&format!("c {} {}\n", seconds, ready)),
+            }
+        }
+        fs::write(path, out)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ReplayError> {
+        let content = fs::read_to_string(path)?;
+        let mut lines = content.lines().enumerate();
+        let version = match lines.next().and_then(|(_, l)| l.split_once(' ')) {
+            Some((HEADER, v)) => v.trim().parse::<u32>().map_err(|_| ReplayError::BadHeader)?,
+            _ => return Err(ReplayError::BadHeader),
+        };
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::Version(version));
+        }
+        let mut replay = Replay::new(GameMode::Classic, GameSpeed::Medium, Level::Normal, 0);
+        for (i, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let ok = match line.split_once('=') {
+                Some((key, value)) => replay.apply_header(key, value),
+                None => match parse_step(line) {
+                    Some(step) => {
+                        replay.steps.push(step);
+                        true
+                    }
+                    None => false,
+                },
+            };
+            if !ok {
+                return Err(ReplayError::Invalid(i + 1));
+            }
+        }
+        Ok(replay)
+    }
+
+    fn apply_header(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "mode" => GameMode::from_key(value).map(|m| self.mode = m).is_some(),
+            "speed" => GameSpeed::from_key(value).map(|s| self.speed = s).is_some(),
+            "level" => Level::from_key(value).map(|l| self.level = l).is_some(),
+            "seed" => value.parse().map(|s| self.seed = s).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+fn parse_step(line: &str) -> Option<ReplayStep> {
+    let mut parts = line.split_whitespace();
+    let step = match parts.next()? {
+        "u" => ReplayStep::Update(parts.next()?.parse().ok().filter(|dt: &f64| dt.is_finite() && *dt >= 0.0)?),
+        "t" => ReplayStep::Turn(parse_direction(parts.next()?)?),
+        "c" => ReplayStep::Countdown(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?),
+        _ => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(step)
+}
+
+// 逐帧播放回放：每次取出一帧的操作，由调用者执行
+pub struct ReplayPlayer {
+    replay: Replay,
+    pos: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer { replay, pos: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    // 取出下一帧的操作（到下一个 Update 为止，含其前面的转向与倒数）；回放已经结束时返回 None
+    pub fn next_frame(&mut self) -> Option<&[ReplayStep]> {
+        let steps = &self.replay.steps[self.pos..];
+        let len = steps.iter().position(|s| matches!(s, ReplayStep::Update(_)))? + 1;
+        self.pos += len;
+        Some(&steps[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AiKind;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("snake-replay-test-{}-{}.replay", std::process::id(), name))
+    }
+
+    fn load_text(name: &str, content: &str) -> Result<Replay, ReplayError> {
+        let path = temp_path(name);
+        fs::write(&path, content).unwrap();
+        let result = Replay::load(&path);
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    // 像窗口版一样录制一局：倒数后由贪心 AI 转向，每帧推进 dt
+    fn record(seed: u64) -> (Replay, Game) {
+        let mut replay = Replay::new(GameMode::Speed, GameSpeed::Fast, Level::Small, seed);
+        let mut game = replay.start();
+        let mut ai = AiKind::Greedy.create(seed);
+        let mut push = |game: &mut Game, step: ReplayStep| {
+            step.apply(game);
+            replay.steps.push(step);
+        };
+        push(&mut game, ReplayStep::Countdown(3, true));
+        for frame in 0..3000 {
+            if game.is_game_over() {
+                break;
+            }
+            if !game.is_counting_down() {
+                if let Some(d) = ai.decide(&game) {
+                    push(&mut game, ReplayStep::Turn(d));
+                }
+            }
+            // 帧长不固定，检验 dt 原样保存
+            push(&mut game, ReplayStep::Update(1.0 / 60.0 + (frame % 7) as f64 * 0.001));
+        }
+        (replay, game)
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let (replay, _) = record(5);
+        assert!(replay.steps.iter().any(|s| matches!(s, ReplayStep::Turn(_))));
+        assert!(replay.steps.iter().any(|s| matches!(s, ReplayStep::Countdown(..))));
+        let path = temp_path("round-trip");
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.unwrap(), replay);
+    }
+
+    #[test]
+    fn malformed_lines_rejected() {
+        let head = "snake-replay 1\nmode=classic\n";
+        let cases = [
+            ("mode=fastest\n", 3),
+            ("seed=-1\n", 3),
+            ("colour=red\n", 3),
+            ("u 0.1\nu -0.1\n", 4),
+            ("u inf\n", 3),
+            ("u 0.1\nt north\n", 4),
+            ("c 3\n", 3),
+            ("c 3 yes\n", 3),
+            ("u 0.1 0.2\n", 3),
+            ("\nx 1\n", 4), // 空行也计入行号
+        ];
+        for (i, (body, line)) in cases.iter().enumerate() {
+            match load_text(&format!("invalid-{}", i), &format!("{}{}", head, body)) {
+                Err(ReplayError::Invalid(l)) => assert_eq!(l, *line, "{:?}", body),
+                other => panic!("{:?} 应在第 {} 行出错，实际 {:?}", body, line, other),
+            }
+        }
+        assert!(matches!(load_text("header", "snake-save 1\n"), Err(ReplayError::BadHeader)));
+        assert!(matches!(load_text("version", "snake-replay 9\n"), Err(ReplayError::Version(9))));
+    }
+
+    #[test]
+    fn replaying_reproduces_game() {
+        let (replay, recorded) = record(11);
+        assert!(recorded.get_score() > 0);
+        let updates = replay.steps.iter().filter(|s| matches!(s, ReplayStep::Update(_))).count();
+        let mut game = replay.start();
+        let mut player = ReplayPlayer::new(replay);
+        let mut frames = 0;
+        while let Some(steps) = player.next_frame() {
+            // 每帧以一个 Update 结尾
+            assert!(matches!(steps.last(), Some(ReplayStep::Update(_))));
+            for &step in steps {
+                step.apply(&mut game);
+            }
+            frames += 1;
+        }
+        assert_eq!(frames, updates);
+        assert_eq!(game.get_score(), recorded.get_score());
+        assert_eq!(game.snake_segments(), recorded.snake_segments());
+        assert_eq!(game.is_game_over(), recorded.is_game_over());
+    }
+}
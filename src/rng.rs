@@ -0,0 +1,56 @@
+use rand::{thread_rng, Rng};
+
+// 游戏内使用的随机数发生器（xorshift64*）。状态只有一个 u64，
+// 可以保存进存档并原样恢复，同一种子产生的食物位置完全相同
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        // 状态不能为 0，否则永远输出 0
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        GameRng {
+            state: if state == 0 { 0x9E37_79B9_7F4A_7C15 } else { state },
+        }
+    }
+
+    // 用系统随机数作种子
+    pub fn from_entropy() -> GameRng {
+        GameRng::new(thread_rng().gen())
+    }
+
+    pub fn from_state(state: u64) -> Option<GameRng> {
+        if state == 0 {
+            None
+        } else {
+            Some(GameRng { state })
+        }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // [low, high) 内的整数
+    pub fn gen_range(&mut self, low: i32, high: i32) -> i32 {
+        let span = (high - low).max(1) as u64;
+        low + (self.next_u64() % span) as i32
+    }
+
+    // [low, high) 内的小数
+    pub fn gen_f64(&mut self, low: f64, high: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        low + unit * (high - low)
+    }
+}
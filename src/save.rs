@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::game::Game;
+use crate::hud::Hud;
+use crate::stats::RunStats;
+
+pub const SAVE_FILE: &str = "save.cfg";
+// 存档格式版本，字段含义变化时加一；读到其他版本的存档直接拒绝
+pub const SAVE_VERSION: u32 = 1;
+const HEADER: &str = "snake-save";
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    BadHeader,
+    Version(u32),
+    Checksum,
+    Missing(String),
+    Invalid(String), // 字段存在但内容不合法
+}
+
+impl SaveError {
+    // 显示在界面上的提示（界面字体只支持英文）
+    pub fn label(&self) -> String {
+        match self {
+            SaveError::Io(_) => "CANNOT READ SAVE FILE".to_string(),
+            SaveError::Version(v) => format!("UNSUPPORTED SAVE VERSION {}", v),
+            _ => "SAVE FILE IS CORRUPTED".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::BadHeader => write!(f, "不是存档文件"),
+            SaveError::Version(v) => write!(f, "不支持的存档版本 {}", v),
+            SaveError::Checksum => write!(f, "校验和不匹配"),
+            SaveError::Missing(key) => write!(f, "缺少字段 {}", key),
+            SaveError::Invalid(key) => write!(f, "字段 {} 无效", key),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+// 保存进行中的一局：游戏状态、本局统计与右侧提示
+pub fn save_session<P: AsRef<Path>>(path: P, game: &Game, run: &RunStats, hud: &Hud) -> io::Result<()> {
+    let mut w = SaveWriter::new();
+    game.save_state(&mut w);
+    run.save_state(&mut w);
+    hud.save_state(&mut w);
+    w.write(path)
+}
+
+// 读取存档；只有全部内容都有效时才修改 hud
+pub fn load_session<P: AsRef<Path>>(path: P, hud: &mut Hud) -> Result<(Game, RunStats), SaveError> {
+    let r = SaveReader::read(path)?;
+    let game = Game::load_state(&r)?;
+    let run = RunStats::load_state(&r)?;
+    if run.mode != game.get_game_mode() {
+        return Err(SaveError::Invalid("stats.mode".to_string()));
+    }
+    hud.load_state(&r)?;
+    Ok((game, run))
+}
+
+// 按顺序写入 key=value，最后附上校验和
+#[derive(Debug, Default)]
+pub struct SaveWriter {
+    body: String,
+}
+
+impl SaveWriter {
+    pub fn new() -> SaveWriter {
+        SaveWriter::default()
+    }
+
+    pub fn put<T: fmt::Display>(&mut self, key: &str, value: T) {
+        self.body.push_str(&format!("{}={}\n", key, value));
+    }
+
+    // 坐标列表写成 x,y;x,y
+    pub fn put_points(&mut self, key: &str, points: &[(i32, i32)]) {
+        let list: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        self.put(key, list.join(";"));
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let content = format!("{} {}\n{}", HEADER, SAVE_VERSION, self.body);
+        fs::write(path, format!("{}checksum={:016x}\n", content, checksum(&content)))
+    }
+}
+
+// 读取并校验存档，按 key 取值
+#[derive(Debug)]
+pub struct SaveReader {
+    values: HashMap<String, String>,
+}
+
+impl SaveReader {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<SaveReader, SaveError> {
+        let content = fs::read_to_string(path)?;
+        SaveReader::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<SaveReader, SaveError> {
+        let mut lines = content.lines();
+        let header = lines.next().ok_or(SaveError::BadHeader)?;
+        let version = match header.split_once(' ') {
+            Some((HEADER, v)) => v.trim().parse::<u32>().map_err(|_| SaveError::BadHeader)?,
+            _ => return Err(SaveError::BadHeader),
+        };
+        if version != SAVE_VERSION {
+            return Err(SaveError::Version(version));
+        }
+        // 校验和覆盖它之前的全部内容
+        let pos = content.rfind("checksum=").ok_or(SaveError::Checksum)?;
+        let expected = u64::from_str_radix(content[pos + "checksum=".len()..].trim(), 16).map_err(|_| SaveError::Checksum)?;
+        if checksum(&content[..pos]) != expected {
+            return Err(SaveError::Checksum);
+        }
+        let values = content[..pos]
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Ok(SaveReader { values })
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, SaveError> {
+        let raw = self.values.get(key).ok_or_else(|| SaveError::Missing(key.to_string()))?;
+        raw.parse().map_err(|_| SaveError::Invalid(key.to_string()))
+    }
+
+    // 取可选值，写入时用空字符串表示"无"
+    pub fn get_optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, SaveError> {
+        match self.values.get(key) {
+            Some(raw) if raw.is_empty() => Ok(None),
+            Some(_) => self.get(key).map(Some),
+            None => Err(SaveError::Missing(key.to_string())),
+        }
+    }
+
+    pub fn get_points(&self, key: &str) -> Result<Vec<(i32, i32)>, SaveError> {
+        let raw = self.values.get(key).ok_or_else(|| SaveError::Missing(key.to_string()))?;
+        if raw.is_empty() {
+            return Ok(Vec::new());
+        }
+        raw.split(';')
+            .map(|p| {
+                let (x, y) = p.split_once(',')?;
+                Some((x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| SaveError::Invalid(key.to_string()))
+    }
+
+    // 以 prefix 开头的所有 key（用于不定数量的条目）
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut keys: Vec<String> = self.values.keys().filter(|k| k.starts_with(prefix)).cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+// FNV-1a 64 位哈希，用来发现被截断或改动过的存档
+fn checksum(content: &str) -> u64 {
+    content.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+// 把 Option 写成存档中的值：None 写为空字符串
+pub fn optional<T: fmt::Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,258 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ai::AiKind;
+use crate::effects::Effects;
+use crate::events::{self, EventListener, GameEvent};
+use crate::game::{Game, Level};
+use crate::hud::Hud;
+use crate::menu::{GameMode, GameSpeed, Menu, MenuState};
+use crate::render::{RecordingRenderer, Renderer, SoftwareRenderer, Transform};
+use crate::sim::{Driver, SIM_DT};
+use crate::stats::RunStats;
+use crate::theme::Theme;
+
+// snake render 可选的界面
+pub const SCREENS: [(&str, MenuState); 12] = [
+    ("main", MenuState::Main),
+    ("modes", MenuState::ModeSelection),
+    ("speeds", MenuState::SpeedSelection),
+    ("confirm", MenuState::ConfirmStart),
+    ("settings", MenuState::Settings),
+    ("profile", MenuState::Profile),
+    ("achievements", MenuState::Achievements),
+    ("players", MenuState::Players),
+    ("controls", MenuState::Controls),
+    ("playing", MenuState::Playing),
+    ("game-menu", MenuState::GameMenu),
+    ("game-over", MenuState::GameOver),
+];
+
+pub fn screen_from_key(key: &str) -> Option<MenuState> {
+    SCREENS.iter().find(|(k, _)| *k == key).map(|&(_, s)| s)
+}
+
+// 画出完整的一帧：窗口和离屏渲染共用
+pub fn draw_frame(r: &mut dyn Renderer, menu: &Menu, game: Option<&Game>, effects: &Effects, hud: &Hud) {
+    r.clear(Theme::from_palette(menu.settings.palette).background);
+    match menu.state {
+        MenuState::Main | MenuState::ModeSelection | MenuState::SpeedSelection | MenuState::ConfirmStart | MenuState::Settings | MenuState::Profile | MenuState::Achievements | MenuState::Players | MenuState::Controls => {
+            menu.draw(r);
+        }
+        MenuState::Playing => {
+            if let Some(game) = game {
+                game.draw(r, &menu.settings);
+                let (board_w, board_h) = game.board_size();
+                effects.draw(game.layout(), board_w, board_h, r);
+                // 绘制分数
+                menu.draw_score(game.get_score(), r);
+                // 限时模式：绘制倒计时
+                if let Some(rt) = game.get_remaining_time() {
+                    let min = (rt as i32) / 60;
+                    let s = (rt as i32) % 60;
+                    let time_text = format!("TIME {:02}:{:02}", min, s);
+                    menu.draw_text_top_right(&time_text, 24.0, [1.0, 1.0, 0.0, 1.0], r);
+                }
+                // 绘制暂停指示器
+                menu.draw_pause_indicator(r);
+                menu.draw_countdown(game.countdown_phase(), r);
+                // 绘制游戏消息
+                hud.draw(game.layout(), board_w, r, &menu.settings);
+            }
+            menu.draw(r); // 绘制游戏内菜单按钮
+        }
+        MenuState::GameMenu | MenuState::GameOver => {
+            if let Some(game) = game {
+                game.draw(r, &menu.settings);
+            }
+            menu.draw(r); // 绘制游戏内菜单或GameOver菜单
+        }
+    }
+}
+
+// 离屏渲染的参数；游戏画面由自动驾驶从 seed 开始玩到第 tick 格
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub screen: MenuState,
+    pub mode: GameMode,
+    pub speed: GameSpeed,
+    pub level: Level,
+    pub seed: u64,
+    pub tick: u64,
+    pub window_size: (u32, u32),
+    pub scale: f64,
+    pub calls: bool, // 输出绘制调用而不是像素摘要
+    pub output: Option<PathBuf>, // 写入 PNG 的路径
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            screen: MenuState::Playing,
+            mode: GameMode::Classic,
+            speed: GameSpeed::Medium,
+            level: Level::Normal,
+            seed: 0,
+            tick: 0,
+            window_size: (800, 600),
+            scale: 1.0,
+            calls: false,
+            output: None,
+        }
+    }
+}
+
+// 不读取任何玩家文件，同样的参数总是得到同样的画面
+pub struct Scene {
+    pub menu: Menu,
+    pub game: Option<Game>,
+    pub effects: Effects,
+    pub hud: Hud,
+}
+
+impl Scene {
+    pub fn new(options: &RenderOptions) -> Scene {
+        let (w, h) = (options.window_size.0 as f64, options.window_size.1 as f64);
+        let mut menu = Menu::new(w, h);
+        menu.selected_mode = options.mode;
+        menu.selected_speed = options.speed;
+        menu.state = options.screen;
+        let mut effects = Effects::new();
+        effects.set_seed(options.seed);
+        effects.set_theme(Theme::from_palette(menu.settings.palette));
+        let mut hud = Hud::new(w, h);
+        hud.apply_settings(&menu.settings);
+        let mut scene = Scene { menu, game: None, effects, hud };
+        if matches!(options.screen, MenuState::Playing | MenuState::GameMenu | MenuState::GameOver) {
+            scene.play_to_tick(options);
+        }
+        scene
+    }
+
+    fn play_to_tick(&mut self, options: &RenderOptions) {
+        let (board_w, board_h) = options.level.board_size();
+        let mut game = Game::new_seeded(board_w, board_h, options.mode, options.speed, options.seed);
+        game.update_window_size(self.menu.window_width, self.menu.window_height);
+        let mut run = RunStats::new(options.mode, game.speed(), game.snake_length(), game.board_cells());
+        let mut driver = Driver::new(game, AiKind::Safe.create(options.seed));
+        let mut tick = 0;
+        while tick < options.tick && !driver.game.is_game_over() {
+            driver.frame(SIM_DT);
+            run.update(SIM_DT);
+            self.effects.update(SIM_DT);
+            self.hud.update(SIM_DT);
+            let events = driver.game.drain_events();
+            tick += events.iter().filter(|e| matches!(e, GameEvent::TickAdvanced { .. })).count() as u64;
+            let listeners: &mut [&mut dyn EventListener] = &mut [&mut self.effects, &mut self.hud, &mut run];
+            events::dispatch(&events, listeners);
+        }
+        self.menu.last_run = Some(run);
+        self.game = Some(driver.game);
+    }
+
+    pub fn draw(&self, r: &mut dyn Renderer) {
+        draw_frame(r, &self.menu, self.game.as_ref(), &self.effects, &self.hud);
+    }
+}
+
+// 在内存中画出一帧，scale 放大整个画面
+pub fn rasterize(menu: &Menu, game: Option<&Game>, effects: &Effects, hud: &Hud, scale: f64) -> SoftwareRenderer {
+    let w = (menu.window_width * scale).round() as u32;
+    let h = (menu.window_height * scale).round() as u32;
+    let mut r = SoftwareRenderer::new(w, h);
+    r.push_transform(Transform { offset: [0.0, 0.0], scale });
+    draw_frame(&mut r, menu, game, effects, hud);
+    r.pop_transform();
+    r
+}
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+// screenshots/ 下以毫秒时间戳命名的新文件
+pub fn capture_path(extension: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(SCREENSHOT_DIR)?;
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+    Ok(PathBuf::from(SCREENSHOT_DIR).join(format!("snake-{}.{}", millis, extension)))
+}
+
+// 游戏中按 F12：把当前画面写成 PNG
+pub fn save_screenshot(menu: &Menu, game: Option<&Game>, effects: &Effects, hud: &Hud) -> image::ImageResult<PathBuf> {
+    let path = capture_path("png")?;
+    rasterize(menu, game, effects, hud, 1.0).save_png(&path)?;
+    Ok(path)
+}
+
+// FNV-1a，像素完全相同时摘要相同
+fn digest(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// snake render：输出绘制调用，或画面尺寸与像素摘要；给出路径时同时写入 PNG
+pub fn run(options: &RenderOptions) -> image::ImageResult<String> {
+    let scene = Scene::new(options);
+    if options.calls {
+        let mut r = RecordingRenderer::new();
+        scene.draw(&mut r);
+        return Ok(r.to_string());
+    }
+    let r = rasterize(&scene.menu, scene.game.as_ref(), &scene.effects, &scene.hud, options.scale);
+    let summary = format!("{}x{} {:016x}", r.width(), r.height(), digest(r.pixels()));
+    match options.output {
+        Some(ref path) => {
+            r.save_png(path)?;
+            Ok(format!("{} 已保存到 {}\n", summary, path.display()))
+        }
+        None => Ok(format!("{}\n", summary)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 软件光栅化的像素摘要；改动 Game::draw 或菜单绘制后，用 snake render 的输出核对画面再更新
+    #[test]
+    fn playing_frame_digest() {
+        let options = RenderOptions { seed: 3, tick: 200, ..RenderOptions::default() };
+        assert_eq!(run(&options).unwrap(), "800x600 c82f6d6205f73ad2\n");
+    }
+
+    #[test]
+    fn game_over_frame_digest() {
+        let options = RenderOptions { screen: MenuState::GameOver, seed: 3, tick: 400, scale: 0.5, ..RenderOptions::default() };
+        assert_eq!(run(&options).unwrap(), "400x300 9273189ba2cec6c0\n");
+    }
+
+    // 绘制调用的基准输出在 tests/golden/ 下；有意改动界面后用
+    // snake render --screen <界面> --calls > tests/golden/<界面>.txt 重新生成，并检查差异
+    fn assert_calls(options: RenderOptions, golden: &str) {
+        let calls = run(&RenderOptions { calls: true, ..options }).unwrap();
+        assert_eq!(calls, golden);
+    }
+
+    #[test]
+    fn main_menu_calls() {
+        let options = RenderOptions { screen: MenuState::Main, ..RenderOptions::default() };
+        assert_calls(options, include_str!("../tests/golden/main.txt"));
+    }
+
+    #[test]
+    fn settings_calls() {
+        let options = RenderOptions { screen: MenuState::Settings, ..RenderOptions::default() };
+        assert_calls(options, include_str!("../tests/golden/settings.txt"));
+    }
+
+    #[test]
+    fn game_menu_calls() {
+        let options = RenderOptions { screen: MenuState::GameMenu, ..RenderOptions::default() };
+        assert_calls(options, include_str!("../tests/golden/game-menu.txt"));
+    }
+
+    #[test]
+    fn playing_calls() {
+        let options = RenderOptions { seed: 3, tick: 200, ..RenderOptions::default() };
+        assert_calls(options, include_str!("../tests/golden/playing.txt"));
+    }
+}
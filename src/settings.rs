@@ -0,0 +1,203 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::theme::Palette;
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+// 蛇的绘制风格
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnakeStyle {
+    Classic,  // 每节相同的方块
+    Textured, // 区分头、身体、拐角和尾巴
+}
+
+impl SnakeStyle {
+    pub fn label(&self) -> &'static str {
+        match *self {
+            SnakeStyle::Classic => "CLASSIC",
+            SnakeStyle::Textured => "TEXTURED",
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            SnakeStyle::Classic => "classic",
+            SnakeStyle::Textured => "textured",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<SnakeStyle> {
+        match key {
+            "classic" => Some(SnakeStyle::Classic),
+            "textured" => Some(SnakeStyle::Textured),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> SnakeStyle {
+        match *self {
+            SnakeStyle::Classic => SnakeStyle::Textured,
+            SnakeStyle::Textured => SnakeStyle::Classic,
+        }
+    }
+}
+
+// 玩家设置，以 key=value 的纯文本形式保存
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub palette: Palette,
+    pub shape_cues: bool, // 食物与大食物额外使用不同图案区分，而不只靠颜色
+    pub snake_style: SnakeStyle,
+    pub smooth_movement: bool, // 在两次移动之间插值绘制蛇，而不是逐格跳动
+    // 音量（百分比 0~100）
+    pub master_volume: u32,
+    pub music_volume: u32,
+    pub sfx_volume: u32,
+    pub notification_duration: f64, // 右侧提示显示的秒数
+    pub countdown: u32, // 开局与取消暂停后倒数的秒数，0 表示不倒数
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            palette: Palette::Default,
+            shape_cues: false,
+            snake_style: SnakeStyle::Textured,
+            smooth_movement: true,
+            master_volume: 80,
+            music_volume: 60,
+            sfx_volume: 80,
+            notification_duration: 5.0,
+            countdown: 3,
+        }
+    }
+}
+
+impl Settings {
+    // 读取设置文件；文件不存在或某一项无法识别时使用默认值
+    pub fn load<P: AsRef<Path>>(path: P) -> Settings {
+        let mut settings = Settings::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return settings,
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                settings.apply(key.trim(), value.trim());
+            }
+        }
+        settings
+    }
+
+    fn apply(&mut self, key: &str, value: &str) {
+        match key {
+            "palette" => {
+                if let Some(p) = Palette::from_key(value) {
+                    self.palette = p;
+                }
+            }
+            "shape_cues" => {
+                if let Some(b) = parse_bool(value) {
+                    self.shape_cues = b;
+                }
+            }
+            "snake_style" => {
+                if let Some(st) = SnakeStyle::from_key(value) {
+                    self.snake_style = st;
+                }
+            }
+            "smooth_movement" => {
+                if let Some(b) = parse_bool(value) {
+                    self.smooth_movement = b;
+                }
+            }
+            "master_volume" => {
+                if let Some(v) = parse_volume(value) {
+                    self.master_volume = v;
+                }
+            }
+            "music_volume" => {
+                if let Some(v) = parse_volume(value) {
+                    self.music_volume = v;
+                }
+            }
+            "sfx_volume" => {
+                if let Some(v) = parse_volume(value) {
+                    self.sfx_volume = v;
+                }
+            }
+            "notification_duration" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    if v > 0.0 {
+                        self.notification_duration = v;
+                    }
+                }
+            }
+            "countdown" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.countdown = v.min(9);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        out.push_str(&format!("palette={}\n", self.palette.key()));
+        out.push_str(&format!("shape_cues={}\n", self.shape_cues));
+        out.push_str(&format!("snake_style={}\n", self.snake_style.key()));
+        out.push_str(&format!("smooth_movement={}\n", self.smooth_movement));
+        out.push_str(&format!("master_volume={}\n", self.master_volume));
+        out.push_str(&format!("music_volume={}\n", self.music_volume));
+        out.push_str(&format!("sfx_volume={}\n", self.sfx_volume));
+        out.push_str(&format!("notification_duration={}\n", self.notification_duration));
+        out.push_str(&format!("countdown={}\n", self.countdown));
+        fs::write(path, out)
+    }
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "1" | "on" => Some(true),
+        "false" | "0" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_volume(value: &str) -> Option<u32> {
+    value.parse::<u32>().ok().map(|v| v.min(100))
+}
+
+// 设置页中点击音量时按 10% 递增，超过 100% 回到 0
+pub fn next_volume(volume: u32) -> u32 {
+    if volume >= 100 {
+        0
+    } else {
+        (volume / 10 + 1) * 10
+    }
+}
+
+// 设置页中可选的提示显示时长（秒），点击时依次切换
+const NOTIFICATION_DURATIONS: [f64; 4] = [2.0, 3.0, 5.0, 8.0];
+
+pub fn next_notification_duration(duration: f64) -> f64 {
+    NOTIFICATION_DURATIONS
+        .iter()
+        .copied()
+        .find(|&d| d > duration)
+        .unwrap_or(NOTIFICATION_DURATIONS[0])
+}
+
+// 设置页中可选的倒数秒数，点击时依次切换
+const COUNTDOWNS: [u32; 5] = [0, 1, 2, 3, 5];
+
+pub fn next_countdown(seconds: u32) -> u32 {
+    COUNTDOWNS.iter().copied().find(|&c| c > seconds).unwrap_or(COUNTDOWNS[0])
+}
@@ -0,0 +1,330 @@
+use std::time::Instant;
+
+use crate::ai::{AiKind, Controller};
+use crate::events::{self, DeathCause, EventListener};
+use crate::game::{Game, Level, Tuning};
+use crate::menu::{GameMode, GameSpeed};
+use crate::rng::GameRng;
+use crate::stats::RunStats;
+
+pub const SIM_DT: f64 = 1.0 / 120.0; // 与窗口版默认的更新频率相同
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_key(key: &str) -> Option<OutputFormat> {
+        match key {
+            "table" => Some(OutputFormat::Table),
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimOptions {
+    pub games: u32,
+    pub mode: GameMode,
+    pub speed: GameSpeed,
+    pub level: Level,
+    pub seed: u64, // 第 i 局的种子由它派生，相同参数的两次运行结果完全一致
+    pub ai: AiKind,
+    pub max_time: f64, // 每局最长模拟秒数，超过即判为超时结束
+    pub format: OutputFormat,
+    pub tuning: Tuning,
+}
+
+impl Default for SimOptions {
+    fn default() -> Self {
+        SimOptions {
+            games: 100,
+            mode: GameMode::Classic,
+            speed: GameSpeed::Medium,
+            level: Level::Normal,
+            seed: 0,
+            ai: AiKind::Safe,
+            max_time: 600.0,
+            format: OutputFormat::Table,
+            tuning: Tuning::default(),
+        }
+    }
+}
+
+// 一局游戏加一个自动驾驶。蛇每前进一格只询问一次控制器，和玩家按键的节奏一致
+pub struct Driver<'a> {
+    pub game: Game,
+    controller: Box<dyn Controller + 'a>,
+    last_head: Option<(i32, i32)>,
+}
+
+impl<'a> Driver<'a> {
+    pub fn new(game: Game, controller: Box<dyn Controller + 'a>) -> Driver<'a> {
+        Driver {
+            game,
+            controller,
+            last_head: None,
+        }
+    }
+
+    pub fn frame(&mut self, dt: f64) {
+        let head = self.game.head_position();
+        if self.last_head != Some(head) {
+            self.last_head = Some(head);
+            if let Some(d) = self.controller.decide(&self.game) {
+                self.game.turn(d);
+            }
+        }
+        self.game.update(dt);
+    }
+}
+
+pub fn new_game(options: &SimOptions, seed: u64) -> Game {
+    let (w, h) = options.level.board_size();
+    let mut game = Game::new_seeded(w, h, options.mode, options.speed, seed);
+    game.set_tuning(options.tuning);
+    game
+}
+
+pub fn run_game(options: &SimOptions, seed: u64) -> RunStats {
+    play(options, new_game(options, seed), options.ai.create(seed))
+}
+
+// 由 controller 操控模拟一局，返回本局统计；超过 max_time 仍未结束时 death 为空
+pub fn play<'a>(options: &SimOptions, game: Game, controller: Box<dyn Controller + 'a>) -> RunStats {
+    let mut run = RunStats::new(options.mode, game.speed(), game.snake_length(), game.board_cells());
+    let mut driver = Driver::new(game, controller);
+    while !driver.game.is_game_over() && run.duration < options.max_time {
+        driver.frame(SIM_DT);
+        run.update(SIM_DT);
+        let listeners: &mut [&mut dyn EventListener] = &mut [&mut run];
+        events::dispatch(&driver.game.drain_events(), listeners);
+    }
+    run
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub games: usize,
+    pub mean_score: f64,
+    pub median_score: f64,
+    pub max_score: i32,
+    pub mean_duration: f64,
+    pub median_duration: f64,
+    pub max_duration: f64,
+    pub wall: usize,
+    pub self_collision: usize,
+    pub timeout: usize,
+    pub won: usize,
+    pub time_limit: usize, // 达到 max_time 被截停的局数
+    pub ticks: u64,
+    pub elapsed: f64, // 实际耗时（秒）
+}
+
+impl Summary {
+    pub fn from_runs(runs: &[RunStats], elapsed: f64) -> Summary {
+        let scores: Vec<f64> = runs.iter().map(|r| r.score as f64).collect();
+        let durations: Vec<f64> = runs.iter().map(|r| r.duration).collect();
+        let deaths = |cause: DeathCause| runs.iter().filter(|r| r.death == Some(cause)).count();
+        Summary {
+            games: runs.len(),
+            mean_score: mean(&scores),
+            median_score: median(&scores),
+            max_score: runs.iter().map(|r| r.score).max().unwrap_or(0),
+            mean_duration: mean(&durations),
+            median_duration: median(&durations),
+            max_duration: durations.iter().copied().fold(0.0, f64::max),
+            wall: deaths(DeathCause::Wall),
+            self_collision: deaths(DeathCause::SelfCollision),
+            timeout: deaths(DeathCause::Timeout),
+            won: runs.iter().filter(|r| r.won).count(),
+            time_limit: runs.iter().filter(|r| !r.is_finished()).count(),
+            ticks: runs.iter().map(|r| r.ticks).sum(),
+            elapsed,
+        }
+    }
+
+    // 每秒实际耗时内模拟的移动格数
+    pub fn ticks_per_second(&self) -> f64 {
+        self.ticks as f64 / self.elapsed.max(1e-9)
+    }
+
+    pub fn to_table(&self) -> String {
+        let rows = [
+            ("局数", self.games.to_string()),
+            ("平均得分", format!("{:.2}", self.mean_score)),
+            ("得分中位数", format!("{:.1}", self.median_score)),
+            ("最高得分", self.max_score.to_string()),
+            ("平均存活", format!("{:.1} 秒", self.mean_duration)),
+            ("存活中位数", format!("{:.1} 秒", self.median_duration)),
+            ("最长存活", format!("{:.1} 秒", self.max_duration)),
+            ("撞墙", self.wall.to_string()),
+            ("咬到自己", self.self_collision.to_string()),
+            ("时间耗尽", self.timeout.to_string()),
+            ("获胜", self.won.to_string()),
+            ("达到时长上限", self.time_limit.to_string()),
+            ("移动格/秒", format!("{:.0}", self.ticks_per_second())),
+        ];
+        rows.iter().map(|(k, v)| format!("{:<8}{:>12}\n", k, v)).collect()
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("games", self.games.to_string()),
+            ("mean_score", format!("{:.3}", self.mean_score)),
+            ("median_score", format!("{:.1}", self.median_score)),
+            ("max_score", self.max_score.to_string()),
+            ("mean_duration", format!("{:.3}", self.mean_duration)),
+            ("median_duration", format!("{:.3}", self.median_duration)),
+            ("max_duration", format!("{:.3}", self.max_duration)),
+            ("deaths_wall", self.wall.to_string()),
+            ("deaths_self", self.self_collision.to_string()),
+            ("deaths_timeout", self.timeout.to_string()),
+            ("won", self.won.to_string()),
+            ("time_limit", self.time_limit.to_string()),
+            ("ticks", self.ticks.to_string()),
+            ("ticks_per_second", format!("{:.0}", self.ticks_per_second())),
+        ]
+    }
+
+    // 表头一行、数据一行，方便多次运行的结果拼在一起比较
+    pub fn to_csv(&self) -> String {
+        let fields = self.fields();
+        let header: Vec<&str> = fields.iter().map(|(k, _)| *k).collect();
+        let values: Vec<&str> = fields.iter().map(|(_, v)| v.as_str()).collect();
+        format!("{}\n{}\n", header.join(","), values.join(","))
+    }
+
+    // 所有值都是数字，不需要转义
+    pub fn to_json(&self) -> String {
+        let body: Vec<String> = self.fields().iter().map(|(k, v)| format!("  \"{}\": {}", k, v)).collect();
+        format!("{{\n{}\n}}\n", body.join(",\n"))
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Table => self.to_table(),
+            OutputFormat::Csv => self.to_csv(),
+            OutputFormat::Json => self.to_json(),
+        }
+    }
+}
+
+pub fn run(options: &SimOptions) -> Summary {
+    let mut seeds = GameRng::new(options.seed);
+    let start = Instant::now();
+    let runs: Vec<RunStats> = (0..options.games).map(|_| run_game(options, seeds.next_u64())).collect();
+    Summary::from_runs(&runs, start.elapsed().as_secs_f64())
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snake::Direction;
+
+    fn run_stats(score: i32, duration: f64, death: Option<DeathCause>, won: bool) -> RunStats {
+        let mut run = RunStats::new(GameMode::Classic, GameSpeed::Medium, 3, 100);
+        run.score = score;
+        run.duration = duration;
+        run.death = death;
+        run.won = won;
+        run.ticks = 10;
+        run
+    }
+
+    #[test]
+    fn same_options_give_same_results() {
+        for ai in AiKind::ALL {
+            let options = SimOptions { games: 4, level: Level::Small, max_time: 20.0, ai, ..SimOptions::default() };
+            let (a, b) = (run(&options), run(&options));
+            assert_eq!(Summary { elapsed: 0.0, ..a }, Summary { elapsed: 0.0, ..b }, "{:?}", ai);
+        }
+    }
+
+    #[test]
+    fn summary_from_runs() {
+        let runs = [
+            run_stats(4, 10.0, Some(DeathCause::Wall), false),
+            run_stats(1, 2.0, Some(DeathCause::SelfCollision), false),
+            run_stats(9, 30.0, Some(DeathCause::Wall), false),
+            run_stats(2, 5.0, None, false), // 达到时长上限
+        ];
+        let s = Summary::from_runs(&runs, 1.0);
+        assert_eq!((s.games, s.max_score, s.ticks), (4, 9, 40));
+        assert_eq!((s.mean_score, s.median_score), (4.0, 3.0));
+        assert_eq!((s.mean_duration, s.median_duration, s.max_duration), (11.75, 7.5, 30.0));
+        assert_eq!((s.wall, s.self_collision, s.timeout, s.won, s.time_limit), (2, 1, 0, 0, 1));
+
+        // 奇数局的中位数取中间一局
+        let s = Summary::from_runs(&runs[..3], 1.0);
+        assert_eq!((s.median_score, s.median_duration), (4.0, 10.0));
+        assert_eq!(Summary::from_runs(&[], 1.0).median_score, 0.0);
+    }
+
+    #[test]
+    fn csv_and_json_fields_match() {
+        let s = Summary::from_runs(&[run_stats(3, 4.0, Some(DeathCause::Timeout), false)], 0.5);
+        let csv = s.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let header: Vec<&str> = lines[0].split(',').collect();
+        assert_eq!(header.len(), lines[1].split(',').count());
+        // JSON 的键与 CSV 表头顺序相同
+        let json = s.to_json();
+        let keys: Vec<&str> = json.lines().filter_map(|l| l.trim().strip_prefix('"')?.split('"').next()).collect();
+        assert_eq!(keys, header);
+    }
+
+    // 记下每次被询问时蛇头的位置
+    struct Recorder(Vec<(i32, i32)>);
+
+    impl Controller for Recorder {
+        fn decide(&mut self, game: &Game) -> Option<Direction> {
+            self.0.push(game.head_position());
+            None
+        }
+    }
+
+    #[test]
+    fn driver_asks_once_per_move() {
+        let mut recorder = Recorder(Vec::new());
+        let mut heads: Vec<(i32, i32)> = Vec::new();
+        let mut driver = Driver::new(new_game(&SimOptions::default(), 1), Box::new(&mut recorder));
+        for _ in 0..240 {
+            let head = driver.game.head_position();
+            if heads.last() != Some(&head) {
+                heads.push(head);
+            }
+            driver.frame(SIM_DT);
+        }
+        drop(driver);
+        assert!(heads.len() > 5);
+        assert_eq!(recorder.0, heads);
+    }
+}
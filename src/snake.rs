@@ -1,10 +1,12 @@
 use piston_window::types::Color;
-use piston_window::{Context, G2d};
+use crate::render::Renderer;
 use std::collections::LinkedList;
 
-use crate::draw::draw_block;
-const SNAKE_COLOR: Color = [0.00, 0.80, 0.00, 1.0];
-#[derive(Copy, Clone, PartialEq)]
+use crate::draw::BoardLayout;
+use crate::settings::SnakeStyle;
+use crate::snake_render::{classify_segments, direction_between, draw_piece, shade, Cell, SegmentPiece};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Direction {
     Up,
     Down,
@@ -20,12 +22,34 @@ impl Direction {
             Direction::Right => Direction::Left,
         }
     }
+    // 向左、向右转 90 度后的方向（y 轴朝下）
+    pub fn left(&self) -> Direction {
+        match *self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+    pub fn right(&self) -> Direction {
+        self.left().opposite()
+    }
+    // 沿该方向前进一格时的坐标变化
+    pub fn delta(&self) -> (i32, i32) {
+        match *self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
 }
 #[derive(Debug, Clone)]
 struct Block {
     x: i32,
     y: i32,
 }
+#[derive(Clone)]
 pub struct Snake {
     direction: Direction,
     body: LinkedList<Block>,
@@ -43,22 +67,72 @@ impl Snake {
             tail: None,
         }
     }
-    pub fn draw(&self, con: &Context, g: &mut G2d) {
-        for block in &self.body {
-            draw_block(SNAKE_COLOR, block.x, block.y, con, g);
-        }
-    }
-    
-    pub fn draw_dynamic(&self, con: &Context, g: &mut G2d, block_size: f64) {
-        for block in &self.body {
-            crate::draw::draw_block_dynamic(SNAKE_COLOR, block.x, block.y, block_size, con, g);
+    // 按设置的风格绘制蛇。progress 为距下一次移动的进度（0~1），
+    // 大于 0 时蛇头会向前探出、尾巴向前收拢，让移动看起来连续；碰撞仍按格子计算
+    pub fn draw_styled(&self, style: SnakeStyle, color: Color, layout: BoardLayout, progress: f64, r: &mut dyn Renderer) {
+        let segments = self.segments();
+        let (dx, dy) = self.direction.delta();
+        // 刚吃到食物时尾部坐标重复，此时尾巴不动（蛇在增长）
+        let len = segments.len();
+        let growing = len >= 2 && segments[len - 1] == segments[len - 2];
+        let tail_shift = if progress > 0.0 && !growing && len >= 2 {
+            let (tx, ty) = segments[len - 1];
+            let (fx, fy) = segments[len - 2];
+            Some(((fx - tx) as f64 * progress, (fy - ty) as f64 * progress))
+        } else {
+            None
+        };
+        let head_shift = (dx as f64 * progress, dy as f64 * progress);
+
+        match style {
+            SnakeStyle::Classic => {
+                for (i, &(x, y)) in segments.iter().enumerate() {
+                    let (sx, sy) = match tail_shift {
+                        Some(shift) if i == len - 1 => shift,
+                        _ => (0.0, 0.0),
+                    };
+                    let (px, py) = layout.to_screen(x as f64 + sx, y as f64 + sy);
+                    r.fill_rect(color, [px, py, layout.block_size, layout.block_size]);
+                }
+                if progress > 0.0 {
+                    let (hx, hy) = segments[0];
+                    let (px, py) = layout.to_screen(hx as f64 + head_shift.0, hy as f64 + head_shift.1);
+                    r.fill_rect(color, [px, py, layout.block_size, layout.block_size]);
+                }
+            }
+            SnakeStyle::Textured => {
+                let pieces = classify_segments(&segments, self.direction);
+                // 从尾到头绘制，让头部盖在最上层
+                for (i, &((x, y), piece)) in pieces.iter().enumerate().rev() {
+                    // 相邻两节颜色略有深浅，形成条纹
+                    let seg_color = if i % 2 == 0 { color } else { shade(color, 0.9) };
+                    let cell_at = |sx: f64, sy: f64| {
+                        let (px, py) = layout.to_screen(x as f64 + sx, y as f64 + sy);
+                        Cell { x: px, y: py, size: layout.block_size }
+                    };
+                    match piece {
+                        SegmentPiece::Head(dir) if progress > 0.0 => {
+                            // 蛇头离开的格子用一节身体补上
+                            let back = match pieces.get(1) {
+                                Some(&((bx, by), _)) => direction_between((x, y), (bx, by)),
+                                None => None,
+                            };
+                            draw_piece(SegmentPiece::Body(dir, back.unwrap_or(dir.opposite())), color, cell_at(0.0, 0.0), r);
+                            draw_piece(piece, seg_color, cell_at(head_shift.0, head_shift.1), r);
+                        }
+                        SegmentPiece::Tail(_) => {
+                            let (sx, sy) = tail_shift.unwrap_or((0.0, 0.0));
+                            draw_piece(piece, seg_color, cell_at(sx, sy), r);
+                        }
+                        _ => draw_piece(piece, seg_color, cell_at(0.0, 0.0), r),
+                    }
+                }
+            }
         }
     }
-    
-    pub fn draw_dynamic_with_offset(&self, con: &Context, g: &mut G2d, block_size: f64, offset_x: f64, offset_y: f64) {
-        for block in &self.body {
-            crate::draw::draw_block_dynamic_with_offset(SNAKE_COLOR, block.x, block.y, block_size, offset_x, offset_y, con, g);
-        }
+    // 蛇身所有格子坐标，头在前
+    pub fn segments(&self) -> Vec<(i32, i32)> {
+        self.body.iter().map(|b| (b.x, b.y)).collect()
     }
     pub fn head_position(&self) -> (i32, i32) {
         let head_block = self.body.front().unwrap();
@@ -92,6 +166,17 @@ impl Snake {
         let removed_block = self.body.pop_back().unwrap();
         self.tail = Some(removed_block);
     }
+    // 从存档恢复：body 为蛇头在前的各节坐标，tail 为上一次移动时去掉的尾巴
+    pub fn from_parts(direction: Direction, body: &[(i32, i32)], tail: Option<(i32, i32)>) -> Snake {
+        Snake {
+            direction,
+            body: body.iter().map(|&(x, y)| Block { x, y }).collect(),
+            tail: tail.map(|(x, y)| Block { x, y }),
+        }
+    }
+    pub fn tail_position(&self) -> Option<(i32, i32)> {
+        self.tail.as_ref().map(|b| (b.x, b.y))
+    }
     pub fn head_direction(&self) -> Direction {
         self.direction
     }
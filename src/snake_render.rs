@@ -0,0 +1,159 @@
+use piston_window::types::Color;
+use crate::render::Renderer;
+
+use crate::snake::Direction;
+
+// 身体宽度相对格子的留白比例
+const BODY_MARGIN: f64 = 0.12;
+
+// 蛇身每一节在绘制时的形态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentPiece {
+    Head(Direction),
+    // 身体：记录与前后两节相连的方向（直线时两方向相反，拐角时相互垂直）
+    Body(Direction, Direction),
+    // 尾巴：记录连向身体的方向
+    Tail(Direction),
+}
+
+// 相邻格子之间的方向；两格重合（刚吃到食物时尾部会重复）时返回 None
+pub fn direction_between(from: (i32, i32), to: (i32, i32)) -> Option<Direction> {
+    match (to.0 - from.0, to.1 - from.1) {
+        (0, -1) => Some(Direction::Up),
+        (0, 1) => Some(Direction::Down),
+        (-1, 0) => Some(Direction::Left),
+        (1, 0) => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+// 根据蛇身坐标（头在前）计算每一节的形态
+pub fn classify_segments(segments: &[(i32, i32)], head_dir: Direction) -> Vec<((i32, i32), SegmentPiece)> {
+    // 去掉连续重复的坐标，避免增长过程中尾部出现"断开"
+    let mut cells: Vec<(i32, i32)> = Vec::with_capacity(segments.len());
+    for &pos in segments {
+        if cells.last() != Some(&pos) {
+            cells.push(pos);
+        }
+    }
+    let mut pieces = Vec::with_capacity(cells.len());
+    for (i, &pos) in cells.iter().enumerate() {
+        let piece = if i == 0 {
+            SegmentPiece::Head(head_dir)
+        } else {
+            let to_front = direction_between(pos, cells[i - 1]).unwrap_or(head_dir);
+            if i == cells.len() - 1 {
+                SegmentPiece::Tail(to_front)
+            } else {
+                let to_back = direction_between(pos, cells[i + 1]).unwrap_or(to_front.opposite());
+                SegmentPiece::Body(to_front, to_back)
+            }
+        };
+        pieces.push((pos, piece));
+    }
+    pieces
+}
+
+// 调整颜色亮度（factor > 1 变亮，< 1 变暗）
+pub fn shade(color: Color, factor: f32) -> Color {
+    [
+        (color[0] * factor).min(1.0),
+        (color[1] * factor).min(1.0),
+        (color[2] * factor).min(1.0),
+        color[3],
+    ]
+}
+
+// 以像素为单位的格子位置与大小
+#[derive(Debug, Clone, Copy)]
+pub struct Cell {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+}
+
+// 从格子中心向某一方向伸出到格子边缘的连接块
+fn draw_arm(color: Color, cell: Cell, dir: Direction, width: f64, r: &mut dyn Renderer) {
+    let bs = cell.size;
+    let m = (bs - width) / 2.0;
+    let rect = match dir {
+        Direction::Up => [cell.x + m, cell.y, width, m + width / 2.0],
+        Direction::Down => [cell.x + m, cell.y + bs / 2.0, width, m + width / 2.0],
+        Direction::Left => [cell.x, cell.y + m, m + width / 2.0, width],
+        Direction::Right => [cell.x + bs / 2.0, cell.y + m, m + width / 2.0, width],
+    };
+    r.fill_rect(color, rect);
+}
+
+pub fn draw_piece(piece: SegmentPiece, color: Color, cell: Cell, r: &mut dyn Renderer) {
+    let bs = cell.size;
+    let width = bs * (1.0 - 2.0 * BODY_MARGIN);
+    let m = bs * BODY_MARGIN;
+    match piece {
+        SegmentPiece::Head(dir) => {
+            let head_color = shade(color, 1.15);
+            // 头部比身体略宽
+            let hm = bs * 0.05;
+            r.fill_rect(head_color, [cell.x + hm, cell.y + hm, bs - 2.0 * hm, bs - 2.0 * hm]);
+            draw_arm(head_color, cell, dir.opposite(), width, r);
+            draw_eyes(dir, cell, r);
+        }
+        SegmentPiece::Body(front, back) => {
+            r.fill_rect(color, [cell.x + m, cell.y + m, width, width]);
+            draw_arm(color, cell, front, width, r);
+            draw_arm(color, cell, back, width, r);
+            // 中央花纹，让身体有纹理感
+            let spot = width * 0.35;
+            r.fill_rect(
+                shade(color, 0.8),
+                [cell.x + (bs - spot) / 2.0, cell.y + (bs - spot) / 2.0, spot, spot],
+            );
+        }
+        SegmentPiece::Tail(front) => {
+            // 尾巴从连接处向末端逐渐变细
+            draw_arm(color, cell, front, width, r);
+            let steps = [0.75, 0.5, 0.25];
+            for (i, &ratio) in steps.iter().enumerate() {
+                let w = width * ratio;
+                let along = bs / 2.0 - (i as f64 + 1.0) * bs * 0.12;
+                let (cx, cy) = match front {
+                    Direction::Up => (cell.x + bs / 2.0, cell.y + bs - along),
+                    Direction::Down => (cell.x + bs / 2.0, cell.y + along),
+                    Direction::Left => (cell.x + bs - along, cell.y + bs / 2.0),
+                    Direction::Right => (cell.x + along, cell.y + bs / 2.0),
+                };
+                r.fill_rect(color, [cx - w / 2.0, cy - w / 2.0, w, w]);
+            }
+        }
+    }
+}
+
+// 头部朝向前方的一对眼睛
+fn draw_eyes(dir: Direction, cell: Cell, r: &mut dyn Renderer) {
+    let bs = cell.size;
+    let eye = bs * 0.22;
+    let pupil = eye * 0.5;
+    let front = bs * 0.62; // 眼睛离后方边缘的距离
+    let side = bs * 0.18; // 眼睛离两侧边缘的距离
+    let centers = match dir {
+        Direction::Up => [(side, bs - front), (bs - side, bs - front)],
+        Direction::Down => [(side, front), (bs - side, front)],
+        Direction::Left => [(bs - front, side), (bs - front, bs - side)],
+        Direction::Right => [(front, side), (front, bs - side)],
+    };
+    let (px, py) = match dir {
+        Direction::Up => (0.0, -pupil * 0.4),
+        Direction::Down => (0.0, pupil * 0.4),
+        Direction::Left => (-pupil * 0.4, 0.0),
+        Direction::Right => (pupil * 0.4, 0.0),
+    };
+    for &(ex, ey) in &centers {
+        let cx = cell.x + ex;
+        let cy = cell.y + ey;
+        r.fill_rect([1.0, 1.0, 1.0, 1.0], [cx - eye / 2.0, cy - eye / 2.0, eye, eye]);
+        r.fill_rect(
+            [0.0, 0.0, 0.0, 1.0],
+            [cx - pupil / 2.0 + px, cy - pupil / 2.0 + py, pupil, pupil],
+        );
+    }
+}
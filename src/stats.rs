@@ -0,0 +1,419 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::events::{DeathCause, EventListener, FoodKind, GameEvent};
+use crate::menu::{GameMode, GameSpeed};
+use crate::save::{SaveError, SaveReader, SaveWriter};
+
+pub const RECORDS_FILE: &str = "records.cfg";
+
+// 单局统计，订阅游戏事件累计
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStats {
+    pub mode: GameMode,
+    pub speed: GameSpeed,
+    pub board_cells: usize, // 游戏区域可活动的格子数
+    pub score: i32,
+    pub duration: f64, // 秒
+    pub length: usize, // 当前长度
+    pub max_length: usize,
+    pub foods: u32,
+    pub big_foods: u32,
+    pub big_foods_missed: u32, // 未吃到就消失的大食物
+    pub turns: u32,
+    pub ticks: u64, // 移动的格数
+    pub time_added: i32, // 限时模式获得的加时（秒）
+    pub max_combo: u32,
+    pub death: Option<DeathCause>,
+    pub won: bool, // 占满游戏区域获胜
+}
+
+impl RunStats {
+    pub fn new(mode: GameMode, speed: GameSpeed, initial_length: usize, board_cells: usize) -> RunStats {
+        RunStats {
+            mode,
+            speed,
+            board_cells,
+            score: 0,
+            duration: 0.0,
+            length: initial_length,
+            max_length: initial_length,
+            foods: 0,
+            big_foods: 0,
+            big_foods_missed: 0,
+            turns: 0,
+            ticks: 0,
+            time_added: 0,
+            max_combo: 0,
+            death: None,
+            won: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.death.is_some() || self.won
+    }
+
+    // 存档只发生在对局进行中，结束状态（死亡、获胜）无需保存
+    pub fn save_state(&self, w: &mut SaveWriter) {
+        w.put("stats.mode", self.mode.key());
+        w.put("stats.speed", self.speed.key());
+        w.put("stats.board_cells", self.board_cells);
+        w.put("stats.score", self.score);
+        w.put("stats.duration", self.duration);
+        w.put("stats.length", self.length);
+        w.put("stats.max_length", self.max_length);
+        w.put("stats.foods", self.foods);
+        w.put("stats.big_foods", self.big_foods);
+        w.put("stats.big_foods_missed", self.big_foods_missed);
+        w.put("stats.turns", self.turns);
+        w.put("stats.ticks", self.ticks);
+        w.put("stats.time_added", self.time_added);
+        w.put("stats.max_combo", self.max_combo);
+    }
+
+    pub fn load_state(r: &SaveReader) -> Result<RunStats, SaveError> {
+        let mode = GameMode::from_key(&r.get::<String>("stats.mode")?).ok_or_else(|| SaveError::Invalid("stats.mode".to_string()))?;
+        let speed = GameSpeed::from_key(&r.get::<String>("stats.speed")?).ok_or_else(|| SaveError::Invalid("stats.speed".to_string()))?;
+        let duration: f64 = r.get("stats.duration")?;
+        if !duration.is_finite() || duration < 0.0 {
+            return Err(SaveError::Invalid("stats.duration".to_string()));
+        }
+        Ok(RunStats {
+            mode,
+            speed,
+            board_cells: r.get("stats.board_cells")?,
+            score: r.get("stats.score")?,
+            duration,
+            length: r.get("stats.length")?,
+            max_length: r.get("stats.max_length")?,
+            foods: r.get("stats.foods")?,
+            big_foods: r.get("stats.big_foods")?,
+            big_foods_missed: r.get("stats.big_foods_missed")?,
+            turns: r.get("stats.turns")?,
+            ticks: r.get("stats.ticks")?,
+            time_added: r.get("stats.time_added")?,
+            max_combo: r.get("stats.max_combo")?,
+            death: None,
+            won: false,
+        })
+    }
+
+    // 只在游戏进行中（未暂停、未结束）累计时长
+    pub fn update(&mut self, dt: f64) {
+        if !self.is_finished() {
+            self.duration += dt;
+        }
+    }
+
+    // 平均速度：每秒移动的格数
+    pub fn average_speed(&self) -> f64 {
+        if self.duration > 0.0 {
+            self.ticks as f64 / self.duration
+        } else {
+            0.0
+        }
+    }
+}
+
+impl EventListener for RunStats {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::FoodEaten { kind, points, .. } => {
+                self.score += points;
+                match kind {
+                    FoodKind::Normal => {
+                        self.foods += 1;
+                        self.length += 1;
+                    }
+                    FoodKind::Big => {
+                        self.big_foods += 1;
+                        self.length += 3;
+                    }
+                }
+                self.max_length = self.max_length.max(self.length);
+            }
+            GameEvent::BigFoodExpired { .. } => self.big_foods_missed += 1,
+            GameEvent::Turned { .. } => self.turns += 1,
+            GameEvent::TickAdvanced { .. } => self.ticks += 1,
+            GameEvent::TimeBonus { seconds, .. } => self.time_added += seconds,
+            GameEvent::ComboChanged { combo, .. } => self.max_combo = self.max_combo.max(*combo),
+            GameEvent::Died { cause, segments } => {
+                self.death = Some(*cause);
+                self.max_length = self.max_length.max(segments.len());
+            }
+            GameEvent::Won { segments } => {
+                self.won = true;
+                self.max_length = self.max_length.max(segments.len());
+            }
+            _ => {}
+        }
+    }
+}
+
+// 某个模式下各项的最好成绩（各项独立取最大值）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BestRecord {
+    pub score: i32,
+    pub duration: f64,
+    pub max_length: usize,
+    pub foods: u32,
+    pub max_combo: u32,
+}
+
+impl BestRecord {
+    fn merge(&mut self, run: &RunStats) {
+        self.score = self.score.max(run.score);
+        self.duration = self.duration.max(run.duration);
+        self.max_length = self.max_length.max(run.max_length);
+        self.foods = self.foods.max(run.foods + run.big_foods);
+        self.max_combo = self.max_combo.max(run.max_combo);
+    }
+
+    fn apply(&mut self, field: &str, value: &str) {
+        match field {
+            "score" => self.score = value.parse().unwrap_or(self.score),
+            "duration" => self.duration = value.parse().unwrap_or(self.duration),
+            "max_length" => self.max_length = value.parse().unwrap_or(self.max_length),
+            "foods" => self.foods = value.parse().unwrap_or(self.foods),
+            "max_combo" => self.max_combo = value.parse().unwrap_or(self.max_combo),
+            _ => {}
+        }
+    }
+}
+
+// 各模式的最好成绩，以 mode.key=value 的纯文本形式保存
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Records {
+    best: [BestRecord; 3],
+}
+
+impl Records {
+    pub fn load<P: AsRef<Path>>(path: P) -> Records {
+        let mut records = Records::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return records,
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            let (mode, field) = match key.trim().split_once('.') {
+                Some(mf) => mf,
+                None => continue,
+            };
+            if let Some(mode) = GameMode::from_key(mode) {
+                records.best[mode.index()].apply(field, value.trim());
+            }
+        }
+        records
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for mode in GameMode::ALL.iter() {
+            let b = &self.best[mode.index()];
+            let k = mode.key();
+            out.push_str(&format!("{}.score={}\n", k, b.score));
+            out.push_str(&format!("{}.duration={}\n", k, b.duration));
+            out.push_str(&format!("{}.max_length={}\n", k, b.max_length));
+            out.push_str(&format!("{}.foods={}\n", k, b.foods));
+            out.push_str(&format!("{}.max_combo={}\n", k, b.max_combo));
+        }
+        fs::write(path, out)
+    }
+
+    pub fn best(&self, mode: GameMode) -> BestRecord {
+        self.best[mode.index()]
+    }
+
+    // 记入一局成绩，返回记入前的最好成绩，用于结算界面对比
+    pub fn submit(&mut self, run: &RunStats) -> BestRecord {
+        let previous = self.best(run.mode);
+        self.best[run.mode.index()].merge(run);
+        previous
+    }
+}
+
+// 以 mm:ss 显示时长
+pub fn format_duration(seconds: f64) -> String {
+    let s = seconds.max(0.0) as i32;
+    format!("{:02}:{:02}", s / 60, s % 60)
+}
+
+pub const LIFETIME_FILE: &str = "lifetime.cfg";
+const SCORE_HISTORY_LEN: usize = 20; // 资料页柱状图显示的最近局数
+
+// 跨局累计的统计，以 key=value 的纯文本形式保存
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LifetimeStats {
+    pub games_played: [u32; 3], // 按模式
+    pub total_time: f64,
+    pub total_food: u32,
+    pub longest_snake: usize,
+    pub deaths: [u32; 3], // 撞墙 / 咬到自己 / 超时
+    pub score_history: Vec<(GameMode, i32)>, // 最近若干局，旧的在前
+}
+
+impl LifetimeStats {
+    pub fn load<P: AsRef<Path>>(path: P) -> LifetimeStats {
+        let mut stats = LifetimeStats::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return stats,
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                stats.apply(key.trim(), value.trim());
+            }
+        }
+        stats
+    }
+
+    fn apply(&mut self, key: &str, value: &str) {
+        if let Some(mode) = key.strip_prefix("games.").and_then(GameMode::from_key) {
+            self.games_played[mode.index()] = value.parse().unwrap_or(0);
+            return;
+        }
+        if let Some(cause) = key.strip_prefix("deaths.") {
+            if let Some(i) = DEATH_KEYS.iter().position(|k| *k == cause) {
+                self.deaths[i] = value.parse().unwrap_or(0);
+            }
+            return;
+        }
+        match key {
+            "total_time" => self.total_time = value.parse().unwrap_or(0.0),
+            "total_food" => self.total_food = value.parse().unwrap_or(0),
+            "longest_snake" => self.longest_snake = value.parse().unwrap_or(0),
+            // 形如 classic:12,speed:30
+            "score_history" => {
+                self.score_history = value
+                    .split(',')
+                    .filter_map(|entry| {
+                        let (mode, score) = entry.split_once(':')?;
+                        Some((GameMode::from_key(mode)?, score.parse().ok()?))
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for mode in GameMode::ALL.iter() {
+            out.push_str(&format!("games.{}={}\n", mode.key(), self.games_played[mode.index()]));
+        }
+        out.push_str(&format!("total_time={}\n", self.total_time));
+        out.push_str(&format!("total_food={}\n", self.total_food));
+        out.push_str(&format!("longest_snake={}\n", self.longest_snake));
+        for (i, key) in DEATH_KEYS.iter().enumerate() {
+            out.push_str(&format!("deaths.{}={}\n", key, self.deaths[i]));
+        }
+        let history: Vec<String> = self
+            .score_history
+            .iter()
+            .map(|(mode, score)| format!("{}:{}", mode.key(), score))
+            .collect();
+        out.push_str(&format!("score_history={}\n", history.join(",")));
+        fs::write(path, out)
+    }
+
+    pub fn total_games(&self) -> u32 {
+        self.games_played.iter().sum()
+    }
+
+    pub fn record(&mut self, run: &RunStats) {
+        self.games_played[run.mode.index()] += 1;
+        self.total_time += run.duration;
+        self.total_food += run.foods + run.big_foods;
+        self.longest_snake = self.longest_snake.max(run.max_length);
+        if let Some(cause) = run.death {
+            self.deaths[death_index(cause)] += 1;
+        }
+        self.score_history.push((run.mode, run.score));
+        if self.score_history.len() > SCORE_HISTORY_LEN {
+            let excess = self.score_history.len() - SCORE_HISTORY_LEN;
+            self.score_history.drain(..excess);
+        }
+    }
+}
+
+const DEATH_KEYS: [&str; 3] = ["wall", "self", "timeout"];
+
+fn death_index(cause: DeathCause) -> usize {
+    match cause {
+        DeathCause::Wall => 0,
+        DeathCause::SelfCollision => 1,
+        DeathCause::Timeout => 2,
+    }
+}
+
+pub const HIGH_SCORES_FILE: &str = "highscores.cfg";
+const HIGH_SCORE_ENTRIES: usize = 5;
+
+// 每个玩家自己的排行榜，每个模式保留前几名，以 mode.N=名字:分数 的形式保存
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HighScores {
+    entries: [Vec<(String, i32)>; 3],
+}
+
+impl HighScores {
+    pub fn load<P: AsRef<Path>>(path: P) -> HighScores {
+        let mut scores = HighScores::default();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return scores,
+        };
+        for line in content.lines() {
+            let entry = line.trim().split_once('=').and_then(|(key, value)| {
+                let (mode, _) = key.split_once('.')?;
+                let (name, score) = value.rsplit_once(':')?;
+                Some((GameMode::from_key(mode)?, name.to_string(), score.parse::<i32>().ok()?))
+            });
+            if let Some((mode, name, score)) = entry {
+                scores.insert(mode, &name, score);
+            }
+        }
+        scores
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = String::new();
+        for mode in GameMode::ALL.iter() {
+            for (i, (name, score)) in self.entries[mode.index()].iter().enumerate() {
+                out.push_str(&format!("{}.{}={}:{}\n", mode.key(), i + 1, name, score));
+            }
+        }
+        fs::write(path, out)
+    }
+
+    pub fn entries(&self, mode: GameMode) -> &[(String, i32)] {
+        &self.entries[mode.index()]
+    }
+
+    // 这个分数能否进入排行榜
+    pub fn qualifies(&self, mode: GameMode, score: i32) -> bool {
+        let list = &self.entries[mode.index()];
+        score > 0 && (list.len() < HIGH_SCORE_ENTRIES || list.last().is_some_and(|(_, s)| score > *s))
+    }
+
+    pub fn insert(&mut self, mode: GameMode, name: &str, score: i32) {
+        let list = &mut self.entries[mode.index()];
+        // 同分时先上榜的排在前面
+        let pos = list.iter().position(|(_, s)| score > *s).unwrap_or(list.len());
+        list.insert(pos, (name.to_string(), score));
+        list.truncate(HIGH_SCORE_ENTRIES);
+    }
+}
@@ -0,0 +1,246 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::audio::{Sound, SAMPLE_RATE};
+
+// 芯片音乐风格的基本波形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    Square { duty: f32 }, // duty 为高电平占空比（0~1）
+    Triangle,
+    Sawtooth,
+    Noise,
+}
+
+// ADSR 包络，时间单位为秒，sustain 为保持阶段的音量（0~1）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    // 第 t 秒（总时长 duration）的音量系数
+    pub fn level(&self, t: f32, duration: f32) -> f32 {
+        let release_start = (duration - self.release).max(0.0);
+        let held = if t < self.attack {
+            t / self.attack.max(f32::EPSILON)
+        } else if t < self.attack + self.decay {
+            let k = (t - self.attack) / self.decay.max(f32::EPSILON);
+            1.0 - (1.0 - self.sustain) * k
+        } else {
+            self.sustain
+        };
+        if t >= release_start {
+            let k = ((t - release_start) / self.release.max(f32::EPSILON)).min(1.0);
+            held * (1.0 - k)
+        } else {
+            held
+        }
+    }
+}
+
+// 一个音：波形 + 包络 + 从起始频率滑到结束频率的音高变化
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blip {
+    pub waveform: Waveform,
+    pub freq_start: f32,
+    pub freq_end: f32,
+    pub duration: f32,
+    pub volume: f32,
+    pub envelope: Envelope,
+}
+
+const SHORT_ENVELOPE: Envelope = Envelope { attack: 0.002, decay: 0.03, sustain: 0.6, release: 0.03 };
+
+impl Blip {
+    fn new(waveform: Waveform, freq_start: f32, freq_end: f32, duration: f32, volume: f32) -> Blip {
+        Blip { waveform, freq_start, freq_end, duration, volume, envelope: SHORT_ENVELOPE }
+    }
+
+    fn with_envelope(mut self, envelope: Envelope) -> Blip {
+        self.envelope = envelope;
+        self
+    }
+
+    // 整体升高音高（factor 为频率倍数）
+    fn pitched(mut self, factor: f32) -> Blip {
+        self.freq_start *= factor;
+        self.freq_end *= factor;
+        self
+    }
+}
+
+// 把一个音渲染为单声道 PCM（-1~1）
+pub fn render(blip: &Blip, sample_rate: u32) -> Vec<f32> {
+    let count = (blip.duration * sample_rate as f32) as usize;
+    let mut out = Vec::with_capacity(count);
+    let mut phase = 0.0f32;
+    // 15 位线性反馈移位寄存器产生的噪声，结果可复现
+    let mut lfsr: u16 = 1;
+    let mut noise_value = 1.0f32;
+    for i in 0..count {
+        let t = i as f32 / sample_rate as f32;
+        let k = if count > 1 { i as f32 / (count - 1) as f32 } else { 0.0 };
+        // 指数插值，听感上音高均匀滑动
+        let freq = blip.freq_start * (blip.freq_end / blip.freq_start).powf(k);
+        let prev_phase = phase;
+        phase = (phase + freq / sample_rate as f32).fract();
+        let value = match blip.waveform {
+            Waveform::Square { duty } => {
+                if phase < duty {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+            Waveform::Noise => {
+                // 每个周期更新一次噪声值，频率越高噪声越"尖"
+                if phase < prev_phase {
+                    let bit = (lfsr ^ (lfsr >> 1)) & 1;
+                    lfsr = (lfsr >> 1) | (bit << 14);
+                    noise_value = if lfsr & 1 == 1 { 1.0 } else { -1.0 };
+                }
+                noise_value
+            }
+        };
+        out.push(value * blip.volume * blip.envelope.level(t, blip.duration));
+    }
+    out
+}
+
+// 依次渲染多个音并首尾相接
+pub fn render_sequence(blips: &[Blip], sample_rate: u32) -> Vec<f32> {
+    blips.iter().flat_map(|b| render(b, sample_rate)).collect()
+}
+
+// 各个游戏音效的音色定义。pitch_step 为升高的半音数，连击越多音调越高
+pub fn sound_effect(sound: Sound, pitch_step: u32) -> Vec<f32> {
+    let factor = 2f32.powf(pitch_step as f32 / 12.0);
+    let square = Waveform::Square { duty: 0.5 };
+    let blips: Vec<Blip> = match sound {
+        Sound::Eat => vec![Blip::new(square, 660.0, 990.0, 0.08, 0.35)],
+        // 大三和弦琶音 C-E-G
+        Sound::BigEat => vec![
+            Blip::new(square, 523.0, 523.0, 0.06, 0.35),
+            Blip::new(square, 659.0, 659.0, 0.06, 0.35),
+            Blip::new(square, 784.0, 1046.0, 0.10, 0.35),
+        ],
+        Sound::Turn => vec![Blip::new(Waveform::Triangle, 220.0, 180.0, 0.03, 0.25)],
+        Sound::PowerUp => vec![Blip::new(Waveform::Square { duty: 0.125 }, 300.0, 1200.0, 0.25, 0.3)
+            .with_envelope(Envelope { attack: 0.01, decay: 0.05, sustain: 0.8, release: 0.08 })],
+        Sound::Death => vec![
+            Blip::new(Waveform::Sawtooth, 440.0, 110.0, 0.35, 0.35)
+                .with_envelope(Envelope { attack: 0.005, decay: 0.1, sustain: 0.7, release: 0.1 }),
+            Blip::new(Waveform::Noise, 2000.0, 400.0, 0.3, 0.3)
+                .with_envelope(Envelope { attack: 0.002, decay: 0.05, sustain: 0.6, release: 0.2 }),
+        ],
+        Sound::MenuClick => vec![Blip::new(square, 1000.0, 1000.0, 0.02, 0.25)
+            .with_envelope(Envelope { attack: 0.001, decay: 0.005, sustain: 0.8, release: 0.01 })],
+        Sound::TimeWarning => vec![Blip::new(Waveform::Triangle, 880.0, 880.0, 0.12, 0.4)],
+    };
+    let blips: Vec<Blip> = blips.into_iter().map(|b| b.pitched(factor)).collect();
+    render_sequence(&blips, SAMPLE_RATE)
+}
+
+// 导出为 16 位单声道 PCM 的 WAV 文件
+pub fn write_wav<P: AsRef<Path>>(path: P, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // 单声道
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // 每秒字节数
+    out.extend_from_slice(&2u16.to_le_bytes()); // 每帧字节数
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &s in samples {
+        let v = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    fs::write(path, out)
+}
+
+// 把所有音效导出到目录中，文件名与音效对应
+pub fn export_sound_effects<P: AsRef<Path>>(dir: P) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    for sound in Sound::ALL.iter() {
+        let samples = sound_effect(*sound, 0);
+        write_wav(dir.join(format!("{}.wav", sound.name())), &samples, SAMPLE_RATE)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn envelope_shape() {
+        let env = Envelope { attack: 0.1, decay: 0.1, sustain: 0.5, release: 0.1 };
+        assert_eq!(env.level(0.0, 1.0), 0.0);
+        assert!((env.level(0.1, 1.0) - 1.0).abs() < 1e-4);
+        assert!((env.level(0.5, 1.0) - 0.5).abs() < 1e-4);
+        assert!(env.level(1.0, 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn render_length_and_peak() {
+        let blip = Blip::new(Waveform::Square { duty: 0.5 }, 440.0, 440.0, 0.1, 0.5);
+        let samples = render(&blip, 22050);
+        assert_eq!(samples.len(), 2205);
+        // 方波在包络的最高点达到音量
+        let p = peak(&samples);
+        assert!(p <= 0.5 && p > 0.49, "peak {}", p);
+        assert!(samples[0].abs() < 1e-6 && samples[samples.len() - 1].abs() < 0.02);
+    }
+
+    #[test]
+    fn pitch_step_keeps_length_and_raises_frequency() {
+        let crossings = |s: &[f32]| s.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count() as f32;
+        let low = sound_effect(Sound::TimeWarning, 0);
+        let high = sound_effect(Sound::TimeWarning, 12);
+        assert_eq!(low.len(), high.len());
+        // 升高 12 个半音即频率加倍
+        let ratio = crossings(&high) / crossings(&low);
+        assert!((ratio - 2.0).abs() < 0.05, "ratio {}", ratio);
+        assert!(Sound::ALL.iter().all(|s| peak(&sound_effect(*s, 0)) <= 1.0));
+    }
+
+    #[test]
+    fn wav_header() {
+        let samples = [0.0, 1.0, -1.0, 0.5];
+        let path = std::env::temp_dir().join(format!("snake-synth-test-{}.wav", std::process::id()));
+        write_wav(&path, &samples, 22050).unwrap();
+        let data = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(u32_at(4), 36 + 8);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!((u32_at(16), u16_at(20), u16_at(22)), (16, 1, 1));
+        assert_eq!((u32_at(24), u32_at(28), u16_at(32), u16_at(34)), (22050, 44100, 2, 16));
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32_at(40), 8);
+        assert_eq!(u16_at(46) as i16, i16::MAX);
+        assert_eq!(u16_at(48) as i16, -i16::MAX);
+    }
+}
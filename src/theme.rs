@@ -0,0 +1,132 @@
+use piston_window::types::Color;
+
+// 配色方案：默认配色 + 各类色觉障碍友好配色 + 高对比度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    Default,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Default,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match *self {
+            Palette::Default => "DEFAULT",
+            Palette::Deuteranopia => "DEUTERANOPIA",
+            Palette::Protanopia => "PROTANOPIA",
+            Palette::Tritanopia => "TRITANOPIA",
+            Palette::HighContrast => "HIGH CONTRAST",
+        }
+    }
+
+    // 设置文件中使用的名称
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Palette::Default => "default",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+            Palette::Tritanopia => "tritanopia",
+            Palette::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Palette> {
+        Palette::ALL.iter().copied().find(|p| p.key() == key)
+    }
+
+    // 设置页中点击时循环切换
+    pub fn next(&self) -> Palette {
+        let idx = Palette::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Palette::ALL[(idx + 1) % Palette::ALL.len()]
+    }
+}
+
+// 一套配色中所有会被绘制的颜色
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub background: Color,
+    pub snake: Color,
+    pub food: Color,
+    pub big_food: Color,
+    pub border: Color,
+    pub game_over: Color,
+    // 形状提示（食物上的十字、大食物上的圆环）使用的颜色
+    pub cue: Color,
+    pub message_normal: Color,
+    pub message_big: Color,
+}
+
+impl Theme {
+    pub fn from_palette(palette: Palette) -> Theme {
+        match palette {
+            Palette::Default => Theme {
+                background: [0.5, 0.5, 0.5, 1.0],
+                snake: [0.00, 0.80, 0.00, 1.0],
+                food: [0.80, 0.00, 0.00, 1.0],
+                big_food: [1.0, 0.5, 0.0, 1.0],
+                border: [0.80, 0.00, 0.00, 1.0],
+                game_over: [0.90, 0.00, 0.00, 0.5],
+                cue: [0.0, 0.0, 0.0, 0.6],
+                message_normal: [0.2, 1.0, 0.2, 1.0],
+                message_big: [1.0, 0.6, 0.0, 1.0],
+            },
+            // 红绿色盲：避开红/绿对比，使用蓝/朱红/黄（Okabe-Ito 色板）
+            Palette::Deuteranopia => Theme {
+                background: [0.5, 0.5, 0.5, 1.0],
+                snake: [0.00, 0.45, 0.70, 1.0],
+                food: [0.84, 0.37, 0.00, 1.0],
+                big_food: [0.94, 0.89, 0.26, 1.0],
+                border: [0.80, 0.47, 0.65, 1.0],
+                game_over: [0.10, 0.10, 0.10, 0.6],
+                cue: [0.0, 0.0, 0.0, 0.7],
+                message_normal: [0.34, 0.71, 0.91, 1.0],
+                message_big: [0.94, 0.89, 0.26, 1.0],
+            },
+            // 红色弱：红色会显得很暗，食物改用更亮的橙色
+            Palette::Protanopia => Theme {
+                background: [0.45, 0.45, 0.45, 1.0],
+                snake: [0.00, 0.45, 0.70, 1.0],
+                food: [0.90, 0.62, 0.00, 1.0],
+                big_food: [1.00, 1.00, 1.00, 1.0],
+                border: [0.10, 0.10, 0.30, 1.0],
+                game_over: [0.10, 0.10, 0.10, 0.6],
+                cue: [0.0, 0.0, 0.0, 0.7],
+                message_normal: [0.34, 0.71, 0.91, 1.0],
+                message_big: [0.90, 0.62, 0.00, 1.0],
+            },
+            // 蓝黄色盲：使用红/青对比
+            Palette::Tritanopia => Theme {
+                background: [0.5, 0.5, 0.5, 1.0],
+                snake: [0.00, 0.60, 0.60, 1.0],
+                food: [0.86, 0.15, 0.30, 1.0],
+                big_food: [1.00, 0.75, 0.80, 1.0],
+                border: [0.20, 0.20, 0.20, 1.0],
+                game_over: [0.10, 0.10, 0.10, 0.6],
+                cue: [0.0, 0.0, 0.0, 0.7],
+                message_normal: [0.40, 0.90, 0.90, 1.0],
+                message_big: [1.00, 0.60, 0.70, 1.0],
+            },
+            Palette::HighContrast => Theme {
+                background: [0.0, 0.0, 0.0, 1.0],
+                snake: [1.0, 1.0, 1.0, 1.0],
+                food: [1.0, 1.0, 0.0, 1.0],
+                big_food: [0.0, 1.0, 1.0, 1.0],
+                border: [0.6, 0.6, 0.6, 1.0],
+                game_over: [0.0, 0.0, 0.0, 0.7],
+                cue: [0.0, 0.0, 0.0, 1.0],
+                message_normal: [1.0, 1.0, 1.0, 1.0],
+                message_big: [0.0, 1.0, 1.0, 1.0],
+            },
+        }
+    }
+}
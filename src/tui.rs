@@ -0,0 +1,370 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::panic;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use piston_window::types::Color;
+use piston_window::Key;
+
+use crate::cli::PlayOptions;
+use crate::events::{self, EventListener};
+use crate::game::{CountdownPhase, Game, Level};
+use crate::keybindings::{KeyBindings, KEYS_FILE};
+use crate::menu::{GameMode, GameSpeed};
+use crate::profile::{Profiles, PROFILES_DIR};
+use crate::rng::GameRng;
+use crate::settings::{Settings, SETTINGS_FILE};
+use crate::snake::Direction;
+use crate::snake_render::shade;
+use crate::stats::{format_duration, RunStats};
+use crate::theme::Theme;
+use crate::MAX_FRAME_TIME;
+
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+#[derive(Debug)]
+pub enum TuiError {
+    Io(io::Error),
+    NotATerminal, // stty 无法切换到原始模式（标准输入不是终端）
+    Unsupported,  // 原始模式依赖 stty，只支持类 Unix 系统
+    ProfileNotFound(String),
+}
+
+impl fmt::Display for TuiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuiError::Io(e) => write!(f, "{}", e),
+            TuiError::NotATerminal => write!(f, "标准输入不是终端，或系统没有 stty"),
+            TuiError::Unsupported => write!(f, "终端版只支持 Linux、macOS 等类 Unix 系统"),
+            TuiError::ProfileNotFound(name) => write!(f, "玩家 {} 不存在", name),
+        }
+    }
+}
+
+impl From<io::Error> for TuiError {
+    fn from(e: io::Error) -> Self {
+        TuiError::Io(e)
+    }
+}
+
+type PanicHook = dyn Fn(&panic::PanicHookInfo<'_>) + Sync + Send + 'static;
+
+// 终端处于原始模式期间持有；析构时恢复终端。
+// release 构建 panic = "abort" 不会析构，所以另装一个 panic 钩子先恢复终端再输出错误
+struct RawTerminal {
+    saved: String, // stty -g 的输出
+    previous_hook: Arc<PanicHook>,
+}
+
+impl RawTerminal {
+    fn enter() -> Result<RawTerminal, TuiError> {
+        let saved = stty(&["-g"]).ok_or(TuiError::NotATerminal)?;
+        stty(&["raw", "-echo"]).ok_or(TuiError::NotATerminal)?;
+        let saved = saved.trim().to_string();
+        let previous_hook: Arc<PanicHook> = Arc::from(panic::take_hook());
+        let (mode, previous) = (saved.clone(), previous_hook.clone());
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal(&mode);
+            previous(info);
+        }));
+        // 备用屏幕、隐藏光标、清屏
+        print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+        io::stdout().flush()?;
+        Ok(RawTerminal { saved, previous_hook })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        restore_terminal(&self.saved);
+        // panic 展开时不能修改钩子，此时进程也即将退出
+        if !thread::panicking() {
+            let previous = self.previous_hook.clone();
+            panic::set_hook(Box::new(move |info| previous(info)));
+        }
+    }
+}
+
+fn restore_terminal(saved: &str) {
+    print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+    let _ = io::stdout().flush();
+    let _ = stty(&[saved]);
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).stderr(Stdio::null()).output().ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Input {
+    Turn(Direction),
+    Char(char), // 其他可见字符，先交给按键绑定
+    Pause,
+    Quit,
+}
+
+// 在单独的线程中读取标准输入，把按键转换成 Input
+fn spawn_input() -> Receiver<Input> {
+    let (sender, inputs) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buf = [0u8; 64];
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            for input in parse_input(&buf[..n]) {
+                if sender.send(input).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    inputs
+}
+
+fn parse_input(bytes: &[u8]) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let input = match bytes[i] {
+            // 方向键：ESC [ A/B/C/D（部分终端为 ESC O A/B/C/D）
+            0x1b if i + 2 < bytes.len() && (bytes[i + 1] == b'[' || bytes[i + 1] == b'O') => {
+                i += 2;
+                match bytes[i] {
+                    b'A' => Some(Input::Turn(Direction::Up)),
+                    b'B' => Some(Input::Turn(Direction::Down)),
+                    b'C' => Some(Input::Turn(Direction::Right)),
+                    b'D' => Some(Input::Turn(Direction::Left)),
+                    _ => None,
+                }
+            }
+            0x1b | b' ' => Some(Input::Pause),
+            0x03 => Some(Input::Quit), // Ctrl-C 在原始模式下不再产生信号
+            c if c.is_ascii_alphanumeric() => Some(Input::Char(c.to_ascii_lowercase() as char)),
+            _ => None,
+        };
+        inputs.extend(input);
+        i += 1;
+    }
+    inputs
+}
+
+// 一个格子占两列：背景色加两个字符
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    color: [u8; 3],
+    glyph: &'static str,
+}
+
+fn rgb(color: Color) -> [u8; 3] {
+    let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [c(color[0]), c(color[1]), c(color[2])]
+}
+
+fn board_cells(game: &Game, theme: &Theme, shape_cues: bool) -> Vec<Cell> {
+    let (w, h) = game.board_size();
+    let empty = Cell { color: rgb(theme.background), glyph: "  " };
+    let mut cells = vec![empty; (w * h) as usize];
+    let mut set = |(x, y): (i32, i32), cell: Cell| {
+        if x >= 0 && y >= 0 && x < w && y < h {
+            cells[(y * w + x) as usize] = cell;
+        }
+    };
+    if let Some((bx, by)) = game.big_food_position() {
+        let cell = Cell { color: rgb(theme.big_food), glyph: if shape_cues { "[]" } else { "  " } };
+        for p in [(bx, by), (bx + 1, by), (bx, by + 1), (bx + 1, by + 1)] {
+            set(p, cell);
+        }
+    }
+    if let Some(p) = game.food_position() {
+        set(p, Cell { color: rgb(theme.food), glyph: if shape_cues { "><" } else { "  " } });
+    }
+    // 蛇头颜色较深；死亡后蛇留在原处，标出撞上的位置
+    let segments = game.snake_segments();
+    for &p in segments.iter().skip(1) {
+        set(p, Cell { color: rgb(theme.snake), glyph: "  " });
+    }
+    if let Some(&head) = segments.first() {
+        let glyph = if game.is_game_over() { "XX" } else { "  " };
+        set(head, Cell { color: rgb(shade(theme.snake, 0.6)), glyph });
+    }
+    let border = Cell { color: rgb(theme.border), glyph: "  " };
+    for x in 0..w {
+        set((x, 0), border);
+        set((x, h - 1), border);
+    }
+    for y in 0..h {
+        set((0, y), border);
+        set((w - 1, y), border);
+    }
+    cells
+}
+
+// 只重画与上一帧不同的格子，经 SSH 运行时也不会刷屏
+struct Screen {
+    width: i32,
+    previous: Vec<Option<Cell>>,
+    status: String,
+}
+
+impl Screen {
+    fn new(width: i32, height: i32) -> Screen {
+        Screen {
+            width,
+            previous: vec![None; (width * height) as usize],
+            status: String::new(),
+        }
+    }
+
+    fn draw(&mut self, cells: &[Cell], status: &str) -> io::Result<()> {
+        let mut out = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if self.previous[i] == Some(*cell) {
+                continue;
+            }
+            self.previous[i] = Some(*cell);
+            let (x, y) = (i as i32 % self.width, i as i32 / self.width);
+            let [r, g, b] = cell.color;
+            out.push_str(&format!("\x1b[{};{}H\x1b[30;48;2;{};{};{}m{}", y + 1, x * 2 + 1, r, g, b, cell.glyph));
+        }
+        if status != self.status {
+            let rows = self.previous.len() as i32 / self.width;
+            out.push_str(&format!("\x1b[0m\x1b[{};1H\x1b[2K{}", rows + 1, status));
+            self.status = status.to_string();
+        }
+        if !out.is_empty() {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(out.as_bytes())?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn status_line(game: &Game, run: &RunStats, paused: bool) -> String {
+    let mut status = format!("得分 {}  长度 {}  用时 {}", game.get_score(), game.snake_length(), format_duration(run.duration));
+    if let Some(rt) = game.get_remaining_time() {
+        status.push_str(&format!("  剩余 {:02}:{:02}", rt as i32 / 60, rt as i32 % 60));
+    }
+    let hint = if game.is_game_over() {
+        let cause = run.death.map_or("", |c| c.label());
+        format!("游戏结束 {}   r 重新开始  q 退出", cause)
+    } else if paused {
+        "已暂停   空格 继续  q 退出".to_string()
+    } else {
+        match game.countdown_phase() {
+            Some(CountdownPhase::Ready) => "READY".to_string(),
+            Some(CountdownPhase::Count(n)) => n.to_string(),
+            Some(CountdownPhase::Go) => "GO!".to_string(),
+            None => "空格 暂停  q 退出".to_string(),
+        }
+    };
+    format!("{}   {}", status, hint)
+}
+
+// 在终端中玩：与窗口版使用同一个 Game，只是换了输入与绘制
+pub fn run(options: &PlayOptions) -> Result<Vec<RunStats>, TuiError> {
+    if !cfg!(unix) {
+        return Err(TuiError::Unsupported);
+    }
+    let mut profiles = Profiles::load(PROFILES_DIR);
+    if let Some(ref name) = options.profile {
+        profiles.select(name).map_err(|_| TuiError::ProfileNotFound(name.clone()))?;
+    }
+    let settings = Settings::load(profiles.path(SETTINGS_FILE));
+    let bindings = KeyBindings::load(profiles.path(KEYS_FILE));
+    let theme = Theme::from_palette(settings.palette);
+    let mode = options.mode.unwrap_or(GameMode::Classic);
+    let speed = options.speed.unwrap_or(GameSpeed::Medium);
+    let (board_w, board_h) = options.level.unwrap_or(Level::Normal).board_size();
+    let new_game = || {
+        let seed = options.seed.unwrap_or_else(|| GameRng::from_entropy().next_u64());
+        let mut game = Game::new_seeded(board_w, board_h, mode, speed, seed);
+        game.start_countdown(settings.countdown, true);
+        let run = RunStats::new(mode, game.speed(), game.snake_length(), game.board_cells());
+        (game, run)
+    };
+
+    let _terminal = RawTerminal::enter()?;
+    let inputs = spawn_input();
+    let mut screen = Screen::new(board_w, board_h);
+    let (mut game, mut run) = new_game();
+    let mut finished = Vec::new(); // 已结束各局的统计，退出后输出
+    let mut recorded = false;
+    let mut paused = false;
+    let mut last = Instant::now();
+    loop {
+        for input in inputs.try_iter() {
+            let turn = match input {
+                Input::Turn(d) => Some(d),
+                Input::Char(c) => match bindings.direction_for(Key::from(c as u32)) {
+                    Some(d) => Some(d),
+                    // 没有绑定为方向时，q 退出，r 在结束后重新开始
+                    None if c == 'q' => return Ok(finished),
+                    None if c == 'r' && game.is_game_over() => {
+                        let (g, r) = new_game();
+                        game = g;
+                        run = r;
+                        recorded = false;
+                        None
+                    }
+                    None => None,
+                },
+                Input::Quit => return Ok(finished),
+                Input::Pause if !game.is_game_over() => {
+                    paused = !paused;
+                    if !paused {
+                        game.start_countdown(settings.countdown, false);
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let (Some(d), false) = (turn, paused) {
+                game.turn(d);
+            }
+        }
+
+        let now = Instant::now();
+        let dt = (now - last).as_secs_f64().min(MAX_FRAME_TIME);
+        last = now;
+        if !paused && !game.is_game_over() {
+            let counting = game.is_counting_down();
+            game.update(dt);
+            if !counting {
+                run.update(dt);
+            }
+        }
+        let listeners: &mut [&mut dyn EventListener] = &mut [&mut run];
+        events::dispatch(&game.drain_events(), listeners);
+        if game.is_game_over() && !recorded {
+            finished.push(run.clone());
+            recorded = true;
+        }
+
+        screen.draw(&board_cells(&game, &theme, settings.shape_cues), &status_line(&game, &run, paused))?;
+        thread::sleep(FRAME_TIME.saturating_sub(now.elapsed()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_go_to_bindings_first() {
+        // q、r 只作为字符交给按键绑定，方向键与 Ctrl-C 直接识别
+        assert_eq!(parse_input(b"qR"), vec![Input::Char('q'), Input::Char('r')]);
+        assert_eq!(parse_input(b"\x1b[A\x03"), vec![Input::Turn(Direction::Up), Input::Quit]);
+    }
+}